@@ -7,6 +7,7 @@ use crate::{
 };
 use extobj::{ExtObj, Var, extobj};
 use parking_lot::RwLock;
+use rustc_hash::FxHashSet;
 use std::{
     any::{TypeId, type_name},
     borrow::Cow,
@@ -58,11 +59,19 @@ pub trait EntityAccessor: Entity + Sized + 'static {
             // check if the table is already loaded in the transaction logs.
             Ok(match trx.logs.get(var).and_then(|map| map.get(key)) {
                 Some(v) => v.as_ref(),
-                None => Self::tbl_from(ctx).await?.get(key),
+                None => Self::entity_of(ctx, key).await?,
             })
         })
     }
 
+    /// Gets an entity of the table in the ctx, the lazy tables load it on demand.
+    fn entity_of<'a>(ctx: &'a Ctx, key: &'a Self::Key) -> BoxFuture<'a, Result<Option<&'a Self>>>
+    where
+        ProviderContainer: LoadAll<Self, (), Self::Tbl>,
+    {
+        Box::pin(async move { Ok(Self::tbl_from(ctx).await?.get(key)) })
+    }
+
     fn manual_sync(trx: &mut CtxTransaction, key: Self::Key, new: Option<Self>) {
         trx.logs
             .get_mut_or_default(Self::tbl_var())
@@ -126,7 +135,7 @@ pub trait EntityRemove: EntityAccessor {
             let old = match old_opt.as_ref() {
                 Some(None) => None,
                 Some(Some(old)) => Some(old),
-                None => Self::tbl_from(ctx).await?.get(&k),
+                None => Self::entity_of(ctx, &k).await?,
             };
 
             let Some(old) = old else {
@@ -168,15 +177,74 @@ pub trait EntityRemove: EntityAccessor {
         ProviderContainer: LoadAll<Self, (), Self::Tbl>,
         for<'b> TransactionProvider<'b>: Delete<Self>,
     {
+        let var = Self::tbl_var();
+
         Box::pin(async move {
+            let ctx = trx.ctx;
+            let gate = trx.err_gate.open()?;
+            let _event_depth = trx.track_depth();
             let mut count = 0;
+            let mut removed = Vec::new();
 
-            for key in &*keys {
-                if Self::remove(trx, key.clone()).await? {
-                    count += 1;
+            for k in &*keys {
+                let old_opt: Option<Option<Self>> =
+                    trx.logs.get_mut_or_default(var).insert(k.clone(), None);
+
+                let exists = match old_opt.as_ref() {
+                    Some(old) => old.is_some(),
+                    None => Self::entity_of(ctx, k).await?.is_some(),
+                };
+
+                if !exists {
+                    continue;
+                }
+
+                count += 1;
+
+                Self::removing().call(trx, k).await.inspect_err(|e| {
+                    error!({ error = %e, id = ?k, ty = ?TypeId::of::<Self>() }, "EntityAccessor::removing event error")
+                })?;
+
+                if trx
+                    .logs
+                    .get(var)
+                    .is_some_and(|v| v.get(k).is_some_and(Option::is_none))
+                {
+                    removed.push((k.clone(), old_opt));
                 }
             }
 
+            if removed.is_empty() {
+                gate.close();
+                return Ok(count);
+            }
+
+            // all the keys are sent to the provider at once so it can batch them.
+            let keys = removed.iter().map(|t| t.0.clone()).collect::<Vec<_>>();
+
+            trx.provider().delete_many(&keys).await.inspect_err(
+                |e| error!({ error = %e, ty = ?TypeId::of::<Self>() }, "delete_many error"),
+            )?;
+
+            for (k, old_opt) in removed {
+                let old = match old_opt.as_ref() {
+                    Some(old) => old.as_ref(),
+                    None => Self::entity_of(ctx, &k).await?,
+                };
+
+                let Some(old) = old else {
+                    continue;
+                };
+
+                old.track_remove(&k, trx).await?;
+
+                Self::removed().call(trx, &k, old).await.inspect_err(|e| {
+                    error!({ error = %e, id = ?k, ty = ?TypeId::of::<Self>() }, "EntityAccessor::removed event error")
+                })?;
+            }
+
+            gate.close();
+
             Ok(count)
         })
     }
@@ -223,7 +291,7 @@ pub trait EntityUpsert: EntityAccessor + EntityValidate + PartialEq {
                 .insert(k.clone(), Some(entity));
 
             let old = match old.as_ref() {
-                None => Self::tbl_from(ctx).await?.get(&k),
+                None => Self::entity_of(ctx, &k).await?,
                 Some(None) => None,
                 Some(Some(old)) => Some(old),
             };
@@ -242,15 +310,96 @@ pub trait EntityUpsert: EntityAccessor + EntityValidate + PartialEq {
         ProviderContainer: LoadAll<Self, (), Self::Tbl>,
         for<'b> TransactionProvider<'b>: Upsert<Self>,
     {
+        let var = Self::tbl_var();
+
         Box::pin(async move {
+            let ctx = trx.ctx;
+            let gate = trx.err_gate.open()?;
+            let _event_depth = trx.track_depth();
             let mut count = 0;
 
-            for (key, entity) in entities {
-                if Self::upsert(trx, key, entity).await? {
-                    count += 1;
+            // the keys to save, `true` when the key was not found in the ctx.
+            let mut pending = Vec::new();
+            let mut pending_keys = FxHashSet::default();
+
+            // each row is validated and logged like successive upserts, only the provider
+            // call is deferred.
+            for (k, mut entity) in entities {
+                let old = Self::entity_from(trx, &k).await?;
+                let is_new = old.is_none();
+
+                if old.is_some_and(|e| *e == entity) {
+                    continue;
+                }
+
+                validate_on_change(trx, &k, &mut entity).await?;
+
+                entity.track_insert(&k, trx).await.inspect_err(|e| {
+                    error!({ error = %e, id = ?k, ty = ?TypeId::of::<Self>() }, "track_insert error")
+                })?;
+
+                let old = trx
+                    .logs
+                    .get_mut_or_default(var)
+                    .insert(k.clone(), Some(entity));
+
+                let old = match old.as_ref() {
+                    None => Self::entity_of(ctx, &k).await?,
+                    Some(None) => None,
+                    Some(Some(old)) => Some(old),
+                };
+
+                Self::upserted().call(trx, &k, old).await.inspect_err(|e| error!({ error = %e, id = ?k, ty = ?TypeId::of::<Self>() }, "EntityAccessor::upserted event error"))?;
+
+                if pending_keys.insert(k.clone()) {
+                    pending.push((k, is_new));
+                }
+
+                count += 1;
+            }
+
+            let log = trx.logs.get_mut_or_default(var);
+            let mut inserted = Vec::new();
+            let mut changed = Vec::new();
+
+            // the rows are taken from the log to be saved as they are after the events, a row
+            // removed by an event handler is not saved.
+            for (k, is_new) in pending {
+                match log.remove(&k) {
+                    Some(Some(v)) if is_new => inserted.push((k, v)),
+                    Some(Some(v)) => changed.push((k, v)),
+                    Some(None) => {
+                        log.insert(k, None);
+                    }
+                    None => {}
                 }
             }
 
+            // all the rows are sent to the provider at once so it can batch them.
+            let provider = trx.provider();
+            let mut result = Ok(());
+
+            if !inserted.is_empty() {
+                result = provider.insert_all(&inserted).await.inspect_err(
+                    |e| error!({ error = %e, ty = ?TypeId::of::<Self>() }, "insert_all error"),
+                );
+            }
+
+            if result.is_ok() && !changed.is_empty() {
+                result = provider.upsert_all(&changed).await.inspect_err(
+                    |e| error!({ error = %e, ty = ?TypeId::of::<Self>() }, "upsert_all error"),
+                );
+            }
+
+            trx.logs.get_mut_or_default(var).extend(
+                inserted
+                    .into_iter()
+                    .chain(changed)
+                    .map(|(k, v)| (k, Some(v))),
+            );
+
+            result?;
+            gate.close();
             Ok(count)
         })
     }
@@ -300,7 +449,7 @@ pub trait EntityUpsertMut: EntityAccessor + EntityValidate + PartialEq {
                 .insert(k.clone(), Some(entity));
 
             let old = match old.as_ref() {
-                None => Self::tbl_from(ctx).await?.get(&k),
+                None => Self::entity_of(ctx, &k).await?,
                 Some(None) => None,
                 Some(Some(old)) => Some(old),
             };
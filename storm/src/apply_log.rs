@@ -24,6 +24,9 @@ pub(crate) fn perform_apply_log(ctx: &mut Ctx, mut logs: Logs) -> bool {
         changed |= f(ctx, &mut logs);
     }
 
+    // the subscribers see the tables and indexes with the whole log applied.
+    ctx.subscriptions.send_deferred();
+
     changed
 }
 
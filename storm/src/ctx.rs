@@ -1,22 +1,31 @@
 use crate::{
-    ApplyLog, AsRefAsync, AsyncTryFrom, BoxFuture, CommitEvent, CtxExtObj, Entity, EntityAccessor,
-    EntityRemove, EntityUpsert, EntityUpsertMut, EventDepth, Get, HashTable, Logs,
-    ProviderContainer, RefIntoIterator, Result, Tag, Transaction, TrxErrGate, VecTable,
+    ApplyLog, AsRefAsync, AsyncTryFrom, BoxFuture, CommitEvent, CtxExtObj, CtxTypeInfo, Entity,
+    EntityAccessor, EntityRemove, EntityUpsert, EntityUpsertMut, Error, EventDepth, Get, HashTable,
+    Logs, PartitionedEntity, PartitionedTable, Preload, PreloadTiming, ProviderContainer,
+    RefIntoIterator, Result, Savepoint, SerializedLogs, Tag, Transaction, TrxErrGate, VecTable,
     indexing::AsyncAsIdxTrx,
+    logs::TableLog,
     perform_apply_log,
-    provider::{Delete, LoadAll, LoadArgs, LoadOne, TransactionProvider, Upsert, UpsertMut},
+    provider::{
+        Delete, LoadAll, LoadArgs, LoadMany, LoadManyRows, LoadOne, TransactionProvider, Upsert,
+        UpsertMut,
+    },
     registry::{perform_registration, provide_date},
+    savepoint::{savepoint_name, snapshot_logs},
+    subscriptions::{Change, Subscriptions},
     trx_iter::TblChangedIter,
 };
 use chrono::NaiveDateTime;
 use rustc_hash::FxHashMap;
 use std::{borrow::Cow, collections::hash_map, hash::Hash};
+use tokio::sync::broadcast;
 use uuid::Uuid;
 use version_tag::VersionTag;
 
 pub struct Ctx {
     pub(crate) provider: ProviderContainer,
     pub(crate) ctx_ext_obj: CtxExtObj,
+    pub(crate) subscriptions: Subscriptions,
 }
 
 impl Ctx {
@@ -26,9 +35,47 @@ impl Ctx {
         Ctx {
             provider,
             ctx_ext_obj: CtxExtObj::new(),
+            subscriptions: Subscriptions::default(),
         }
     }
 
+    /// Decodes the logs committed by another process and applies them. Returns true if
+    /// something changed.
+    pub fn apply_serialized_log(&mut self, logs: SerializedLogs) -> Result<bool> {
+        let logs = logs.into_logs()?;
+        Ok(perform_apply_log(self, logs))
+    }
+
+    /// Applies the changes made outside of storm to a loaded table as a log, which updates the
+    /// indexes incrementally. A `None` removes the row and the rows equal to the current ones
+    /// are skipped. Returns true if something changed.
+    pub fn apply_tbl_changes<E, I>(&mut self, changes: I) -> bool
+    where
+        E: EntityAccessor + PartialEq,
+        I: IntoIterator<Item = (E::Key, Option<E>)>,
+    {
+        let Some(tbl) = self.tbl_of_opt::<E>() else {
+            return false;
+        };
+
+        let log: TableLog<E> = changes
+            .into_iter()
+            .filter(|(k, new)| match (tbl.get(k), new) {
+                (Some(old), Some(new)) => old != new,
+                (old, new) => old.is_some() || new.is_some(),
+            })
+            .collect();
+
+        if log.is_empty() {
+            return false;
+        }
+
+        let mut logs = Logs::default();
+        logs.insert(E::tbl_var(), log);
+
+        perform_apply_log(self, logs)
+    }
+
     #[inline]
     pub fn clear_tbl_of<E: EntityAccessor>(&mut self) {
         E::clear(self);
@@ -42,6 +89,19 @@ impl Ctx {
         &self.ctx_ext_obj
     }
 
+    /// Loads a set of tables concurrently, each table being gated independently.
+    ///
+    /// ```ignore
+    /// let timings = ctx.preload::<(User, Role, UserRole)>().await?;
+    /// ```
+    pub fn preload<P: Preload>(&self) -> BoxFuture<'_, Result<Vec<PreloadTiming>>> {
+        let mut futures = Vec::new();
+
+        P::preload(self, &mut futures);
+
+        Box::pin(futures::future::try_join_all(futures))
+    }
+
     #[inline]
     pub fn provider(&self) -> &ProviderContainer {
         &self.provider
@@ -55,6 +115,44 @@ impl Ctx {
         self.as_ref_async()
     }
 
+    /// Reloads a table from the provider and applies the differences with the current table
+    /// as a log, which updates the indexes incrementally instead of dropping them like
+    /// [clear_tbl_of](Self::clear_tbl_of). Returns true if something changed.
+    ///
+    /// A table that is not loaded is left untouched.
+    pub fn refresh_tbl_of<E>(&mut self) -> BoxFuture<'_, Result<bool>>
+    where
+        E: EntityAccessor + PartialEq,
+        ProviderContainer: LoadAll<E, (), FxHashMap<E::Key, E>>,
+    {
+        Box::pin(async move {
+            if self.tbl_of_opt::<E>().is_none() {
+                return Ok(false);
+            }
+
+            let new: FxHashMap<E::Key, E> = self.provider.load_all(&()).await?;
+
+            let removed = self
+                .tbl_of_opt::<E>()
+                .into_iter()
+                .flat_map(|tbl| tbl.ref_iter())
+                .filter(|(k, _)| !new.contains_key(k))
+                .map(|(k, _)| (k.clone(), None))
+                .collect::<Vec<_>>();
+
+            let changes = new.into_iter().map(|(k, v)| (k, Some(v)));
+
+            Ok(self.apply_tbl_changes::<E, _>(changes.chain(removed)))
+        })
+    }
+
+    /// Receives the changes of an entity type once they are applied to this ctx, see
+    /// [ApplyLog::apply_log]. Dropping the receiver unsubscribes.
+    #[inline]
+    pub fn subscribe<E: EntityAccessor>(&self) -> broadcast::Receiver<Change<E::Key>> {
+        self.subscriptions.subscribe::<E>()
+    }
+
     #[inline]
     pub fn tbl_of<E>(&self) -> BoxFuture<'_, Result<&'_ E::Tbl>>
     where
@@ -71,6 +169,22 @@ impl Ctx {
     {
         self.ctx_ext_obj.get(E::tbl_var()).get()
     }
+
+    /// Gets a partition of a [PartitionedTable], loading it on its first access.
+    ///
+    /// ```ignore
+    /// let invoices = ctx.tbl_of_partition::<Invoice>(&2024).await?;
+    /// ```
+    pub fn tbl_of_partition<'a, E>(
+        &'a self,
+        partition: &'a E::Partition,
+    ) -> BoxFuture<'a, Result<&'a HashTable<E>>>
+    where
+        E: CtxTypeInfo + PartitionedEntity<Tbl = PartitionedTable<E>>,
+        ProviderContainer: LoadAll<E, E::Filter, Vec<(E::Key, E)>>,
+    {
+        PartitionedTable::__tbl_from(self).partition(&self.provider, partition)
+    }
 }
 
 impl Default for Ctx {
@@ -115,6 +229,21 @@ where
     }
 }
 
+impl<E: Entity> LoadMany<E> for Ctx
+where
+    E: Entity,
+    ProviderContainer: LoadMany<E>,
+{
+    #[inline]
+    fn load_many_with_args<'a>(
+        &'a self,
+        keys: &'a [E::Key],
+        args: LoadArgs,
+    ) -> BoxFuture<'a, Result<LoadManyRows<E>>> {
+        self.provider.load_many_with_args(keys, args)
+    }
+}
+
 pub struct CtxLocks<'a, L> {
     pub ctx: &'a Ctx,
     pub locks: L,
@@ -212,6 +341,22 @@ where
     }
 }
 
+impl<E, L> LoadMany<E> for CtxLocks<'_, L>
+where
+    E: Entity,
+    L: Send + Sync,
+    ProviderContainer: LoadMany<E>,
+{
+    #[inline]
+    fn load_many_with_args<'b>(
+        &'b self,
+        keys: &'b [E::Key],
+        args: LoadArgs,
+    ) -> BoxFuture<'b, Result<LoadManyRows<E>>> {
+        self.ctx.load_many_with_args(keys, args)
+    }
+}
+
 pub struct CtxTransaction<'a> {
     pub(crate) date: NaiveDateTime,
     pub(crate) err_gate: TrxErrGate,
@@ -219,6 +364,8 @@ pub struct CtxTransaction<'a> {
     pub(crate) user_id: Uuid,
     depth: EventDepth,
     provider: TransactionProvider<'a>,
+    savepoint_seq: usize,
+    savepoints: Vec<usize>,
     pub ctx: &'a Ctx,
 }
 
@@ -227,7 +374,7 @@ impl<'a> CtxTransaction<'a> {
         Box::pin(async move {
             self.err_gate.check()?;
             Self::commiting().call(&mut self).await?;
-            self.provider.commit().await?;
+            self.provider.commit_logs(Some(&self.logs)).await?;
             Ok(self.logs)
         })
     }
@@ -248,6 +395,54 @@ impl<'a> CtxTransaction<'a> {
         self.depth.val()
     }
 
+    /// Restores the transaction as it was when the savepoint was created, on all the providers
+    /// and in the logs. The savepoints created after it are released.
+    ///
+    /// The transaction can be used again, even if an error occurred after the savepoint.
+    pub fn rollback_to(&mut self, savepoint: Savepoint) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move {
+            let index = self
+                .savepoints
+                .iter()
+                .position(|id| *id == savepoint.id)
+                .ok_or(Error::SavepointNotFound)?;
+
+            // the transaction stays in error if the providers cannot be rolled back.
+            self.err_gate = TrxErrGate::default();
+            let gate = self.err_gate.open()?;
+
+            self.provider
+                .rollback_to(&savepoint_name(savepoint.id))
+                .await?;
+
+            // the index logs are rebuilt from the table logs on demand.
+            self.logs = savepoint.logs;
+            self.savepoints.truncate(index);
+
+            gate.close();
+            Ok(())
+        })
+    }
+
+    /// Creates a savepoint on all the providers and keeps a copy of the table logs. The changes
+    /// made after it can be undone with [rollback_to](Self::rollback_to).
+    ///
+    /// The entities in the logs must be `Clone`, otherwise [Error::SavepointNotSupported] is returned.
+    pub fn savepoint(&mut self) -> BoxFuture<'_, Result<Savepoint>> {
+        Box::pin(async move {
+            self.err_gate.check()?;
+
+            let logs = snapshot_logs(&self.logs)?;
+            let id = self.savepoint_seq;
+
+            self.savepoint_seq += 1;
+            self.provider.savepoint(&savepoint_name(id)).await?;
+            self.savepoints.push(id);
+
+            Ok(Savepoint { id, logs })
+        })
+    }
+
     #[inline]
     pub fn set_date(&mut self, date: NaiveDateTime) {
         self.date = date;
@@ -433,7 +628,7 @@ impl<'a> CtxTransaction<'a> {
 
                 None
             })
-            .collect::<Result<Vec<(E::Key, E)>>>()?;
+            .collect::<Result<LoadManyRows<E>>>()?;
 
         E::upsert_all(self, vec).await?;
 
@@ -486,7 +681,7 @@ impl<'a> CtxTransaction<'a> {
 
                 None
             })
-            .collect::<Result<Vec<(E::Key, E)>>>()?;
+            .collect::<Result<LoadManyRows<E>>>()?;
 
         E::upsert_mut_all(self, vec).await?;
 
@@ -807,6 +1002,8 @@ impl Transaction for async_cell_lock::QueueRwLockQueueGuard<'_, Ctx> {
             err_gate: Default::default(),
             logs: Default::default(),
             provider: self.provider.transaction(),
+            savepoint_seq: 0,
+            savepoints: Vec::new(),
             user_id: user_id.into(),
         }
     }
@@ -1,4 +1,4 @@
-use crate::Fields;
+use crate::{Fields, provider::CommitPhase};
 use std::{
     fmt::{self, Debug, Display},
     mem::{replace, swap},
@@ -9,24 +9,77 @@ pub enum Error {
     AsyncCellLock(async_cell_lock::Error),
     ClientInError,
     ColumnNull,
+
+    /// The row was changed by another writer since it was read, its concurrency token no longer
+    /// matches.
+    ConcurrencyConflict {
+        key: String,
+        table: Box<str>,
+    },
     ConvertFailed(String),
+
+    /// The transaction was chosen as the victim of a deadlock, it can be retried.
+    Deadlock,
     EntityNotFound,
     FieldTooLong {
         len: usize,
         max: usize,
         field: Box<dyn Fields>,
     },
+
+    /// A row references a missing row, or is referenced by the row being deleted.
+    ForeignKeyViolation {
+        constraint: Box<str>,
+        table: Box<str>,
+    },
     Internal,
     Multiple(Vec<Error>),
     NotInTransaction,
     ProviderNotFound,
+
+    /// The savepoint was already rolled back or released by rolling back an earlier savepoint.
+    SavepointNotFound,
+
+    /// A savepoint cannot be created, either because a provider does not support savepoints
+    /// or because the transaction logs contain an entity that is not `Clone`.
+    SavepointNotSupported,
     TransactionError,
+
+    /// A provider failed while committing a transaction.
+    ///
+    /// `committed` lists the providers that were already committed, which can only happen
+    /// in the [CommitPhase::Commit] phase.
+    TransactionCommit {
+        committed: Vec<Box<str>>,
+        error: Box<Error>,
+        phase: CommitPhase,
+        provider: Box<str>,
+    },
     Std(StdError),
     Str(&'static str),
     String(String),
 
+    /// A lock or a query took longer than its timeout.
+    Timeout,
+
+    /// A value is too long for its column, the names are empty when the server does not
+    /// report them.
+    Truncation {
+        column: Box<str>,
+        table: Box<str>,
+    },
+
+    /// A row has the same values as another row for a unique key or index.
+    UniqueViolation {
+        constraint: Box<str>,
+        table: Box<str>,
+    },
+
     #[cfg(feature = "mssql")]
     Mssql(tiberius::error::Error),
+
+    #[cfg(feature = "sqlite")]
+    Sqlite(rusqlite::Error),
 }
 
 impl Error {
@@ -73,6 +126,53 @@ impl Error {
         }
     }
 
+    /// Indicates if the operation can succeed when it is run again, like a deadlock, a lock
+    /// timeout or a connection reset. An error in the [CommitPhase::Commit] phase after some
+    /// providers were committed is never transient, running it again would commit them twice.
+    pub fn is_transient(&self) -> bool {
+        if let Self::TransactionCommit {
+            committed,
+            phase: CommitPhase::Commit,
+            ..
+        } = self
+            && !committed.is_empty()
+        {
+            return false;
+        }
+
+        match self.root() {
+            Self::Deadlock | Self::Timeout => true,
+
+            #[cfg(feature = "mssql")]
+            Self::Mssql(tiberius::error::Error::Io { kind, .. }) => matches!(
+                kind,
+                std::io::ErrorKind::BrokenPipe
+                    | std::io::ErrorKind::ConnectionAborted
+                    | std::io::ErrorKind::ConnectionReset
+                    | std::io::ErrorKind::UnexpectedEof
+            ),
+
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(rusqlite::Error::SqliteFailure(e, _)) => matches!(
+                e.code,
+                rusqlite::ErrorCode::DatabaseBusy | rusqlite::ErrorCode::DatabaseLocked
+            ),
+
+            _ => false,
+        }
+    }
+
+    /// The error of the provider, unwrapping a [TransactionCommit](Self::TransactionCommit) or
+    /// a single error in [Multiple](Self::Multiple), to match on the variants like
+    /// [UniqueViolation](Self::UniqueViolation).
+    pub fn root(&self) -> &Self {
+        match self {
+            Self::Multiple(vec) if vec.len() == 1 => vec.first().map_or(self, Self::root),
+            Self::TransactionCommit { error, .. } => error.root(),
+            e => e,
+        }
+    }
+
     pub(crate) fn extend_one_opt(this: &mut Option<Self>, other: Self) {
         match this {
             Some(e) => e.extend_one(other),
@@ -88,6 +188,14 @@ impl Error {
         }
     }
 
+    #[cfg(feature = "sqlite")]
+    pub fn as_sqlite(&self) -> Option<&rusqlite::Error> {
+        match self {
+            Self::Sqlite(e) => Some(e),
+            _ => None,
+        }
+    }
+
     pub fn std<E: Into<StdError>>(e: E) -> Self {
         Self::Std(e.into())
     }
@@ -104,6 +212,9 @@ impl Debug for Error {
             #[cfg(feature = "mssql")]
             Self::Mssql(e) => Debug::fmt(e, f),
 
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(e) => Debug::fmt(e, f),
+
             e => Display::fmt(e, f),
         }
     }
@@ -116,26 +227,59 @@ impl Display for Error {
             Self::AsyncCellLock(e) => Display::fmt(e, f),
             Self::ClientInError => f.write_str("Client in error state."),
             Self::ColumnNull => f.write_str("Column is null."),
+            Self::ConcurrencyConflict { key, table } => {
+                write!(f, "Concurrency conflict on `{table}`, key: {key}")
+            }
             Self::ConvertFailed(s) => write!(f, "Convert failed: `{s}`"),
+            Self::Deadlock => f.write_str("Deadlock."),
             Self::EntityNotFound => f.write_str("Entity not found."),
             Self::FieldTooLong { len, max, field } => {
                 write!(f, "{field} field too long, len: {len}, max {max}")
             }
+            Self::ForeignKeyViolation { constraint, table } => {
+                write!(f, "Foreign key violation of `{constraint}` on `{table}`.")
+            }
             Self::Multiple(vec) => match &vec[..] {
                 [e] => Display::fmt(&e, f),
                 _ => f.write_str("Multiple errors"),
             },
             Self::TransactionError => f.write_str("Transaction error."),
+            Self::TransactionCommit {
+                committed,
+                error,
+                phase,
+                provider,
+            } => {
+                write!(f, "Provider `{provider}` failed in {phase} phase: {error}")?;
+
+                if !committed.is_empty() {
+                    write!(f, " (already committed: {})", committed.join(", "))?;
+                }
+
+                Ok(())
+            }
             Self::Internal => f.write_str("Internal."),
             Self::NotInTransaction => f.write_str("Not in transaction."),
             Self::ProviderNotFound => f.write_str("Provider not found."),
+            Self::SavepointNotFound => f.write_str("Savepoint not found."),
+            Self::SavepointNotSupported => f.write_str("Savepoint not supported."),
 
             #[cfg(feature = "mssql")]
             Self::Mssql(e) => Display::fmt(e, f),
 
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(e) => Display::fmt(e, f),
+
             Self::Str(e) => Display::fmt(e, f),
             Self::String(e) => Display::fmt(e, f),
             Self::Std(e) => Display::fmt(e, f),
+            Self::Timeout => f.write_str("Timeout."),
+            Self::Truncation { column, table } => {
+                write!(f, "Value truncated in `{table}`, column `{column}`.")
+            }
+            Self::UniqueViolation { constraint, table } => {
+                write!(f, "Unique violation of `{constraint}` on `{table}`.")
+            }
         }
     }
 }
@@ -161,6 +305,13 @@ impl From<tiberius::error::Error> for Error {
     }
 }
 
+#[cfg(feature = "sqlite")]
+impl From<rusqlite::Error> for Error {
+    fn from(e: rusqlite::Error) -> Self {
+        Error::Sqlite(e)
+    }
+}
+
 type StdError = Box<dyn std::error::Error + Send + Sync>;
 
 #[test]
@@ -186,3 +337,15 @@ fn check_downcast() {
 
     e.downcast::<MyErr>().unwrap();
 }
+
+#[test]
+fn check_root() {
+    let e = Error::TransactionCommit {
+        committed: Vec::new(),
+        error: Box::new(Error::Multiple(vec![Error::Deadlock])),
+        phase: CommitPhase::Commit,
+        provider: "".into(),
+    };
+
+    assert!(matches!(e.root(), Error::Deadlock));
+}
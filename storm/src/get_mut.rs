@@ -1,4 +1,8 @@
 use crate::Entity;
+use std::{
+    collections::HashMap,
+    hash::{BuildHasher, Hash},
+};
 
 pub trait GetMut<E: Entity> {
     fn get_mut(&mut self, k: &E::Key) -> Option<&mut E>;
@@ -15,3 +19,14 @@ where
         cache::Cache::get_mut(self, k)
     }
 }
+
+impl<E, S> GetMut<E> for HashMap<E::Key, E, S>
+where
+    E: Entity,
+    E::Key: Eq + Hash,
+    S: BuildHasher,
+{
+    fn get_mut(&mut self, k: &E::Key) -> Option<&mut E> {
+        HashMap::get_mut(self, k)
+    }
+}
@@ -0,0 +1,378 @@
+use crate::{
+    AsRefAsync, BoxFuture, ClearEvent, Clearable, Ctx, CtxTypeInfo, Entity, EntityAccessor,
+    EntityOf, Gc, Get, GetMut, Logs, NotifyTag, ProviderContainer, RefIntoIterator, Result, Tag,
+    Touchable, TouchedEvent,
+    provider::{LoadMany, LoadOne},
+};
+use parking_lot::RwLock;
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::{
+    sync::atomic::{AtomicU64, Ordering::Relaxed},
+    vec::IntoIter,
+};
+use version_tag::VersionTag;
+
+/// A table loading its rows on demand with [LoadOne] instead of loading the whole table, for
+/// the large tables where only a small set of rows is ever read.
+///
+/// The absent keys are also kept so they are not loaded again. When the table holds more than
+/// `CAPACITY` rows, the least recently used rows are evicted when a log is applied and by
+/// [Ctx::gc]. The rows loaded through a shared borrow may still be borrowed, so the table can
+/// grow past `CAPACITY` until the next of those.
+///
+/// Iterating the table only returns the loaded rows. A log changing a row that is not loaded
+/// is applied as an insert, the old value is not loaded and the [Change](crate::Change) reports
+/// no old row.
+pub struct LazyTable<E: Entity, const CAPACITY: usize = 10_000> {
+    lru: AtomicU64,
+    rows: RwLock<FxHashMap<E::Key, Box<LazyRow<E>>>>,
+    tag: VersionTag,
+}
+
+struct LazyRow<E: Entity> {
+    key: E::Key,
+    lru: AtomicU64,
+    value: Option<E>,
+}
+
+impl<E: Entity, const CAPACITY: usize> LazyTable<E, CAPACITY> {
+    pub fn new() -> Self {
+        Self {
+            lru: AtomicU64::new(0),
+            rows: Default::default(),
+            tag: VersionTag::new(),
+        }
+    }
+
+    /// Used by the macro.
+    #[doc(hidden)]
+    pub fn __apply_log(ctx: &mut Ctx, logs: &mut Logs) -> bool
+    where
+        E: CtxTypeInfo + EntityAccessor<Tbl = Self>,
+    {
+        let Some(log) = logs.remove(E::tbl_var()) else {
+            return false;
+        };
+
+        if log.is_empty() {
+            return false;
+        }
+
+        let mut changes = ctx.subscriptions.changes::<E>();
+
+        let Some(tbl) = ctx.ctx_ext_obj.get_mut(E::tbl_var()).get_mut() else {
+            return false;
+        };
+
+        for (k, new) in log {
+            let old = tbl.insert_mut(k.clone(), new);
+            let old = old.as_ref().and_then(|r| r.value.as_ref());
+            let new = tbl.get_mut(&k).map(|e| &*e);
+
+            // a row that was not loaded is applied as an insert.
+            E::applied().call(&k, old, new);
+            changes.push(&k, old.is_some(), new.is_some());
+        }
+
+        tbl.evict();
+        tbl.update_metrics();
+        tbl.tag.notify();
+        ctx.subscriptions.defer(changes);
+        E::touched().call(ctx);
+
+        true
+    }
+
+    /// Used by the macro.
+    #[doc(hidden)]
+    pub fn __entity_of<'a>(ctx: &'a Ctx, k: &'a E::Key) -> BoxFuture<'a, Result<Option<&'a E>>>
+    where
+        E: EntityAccessor<Tbl = Self>,
+        ProviderContainer: LoadOne<E>,
+    {
+        Box::pin(async move { Self::__tbl_from(ctx).get_or_load(&ctx.provider, k).await })
+    }
+
+    /// Used by the macro.
+    #[doc(hidden)]
+    pub fn __tbl_from(ctx: &Ctx) -> &Self
+    where
+        E: EntityAccessor<Tbl = Self>,
+    {
+        ctx.ctx_ext_obj.get(E::tbl_var()).get_or_init(Self::new)
+    }
+
+    /// Gets a row, loading it from the provider if it is not already in the table.
+    pub fn get_or_load<'a, P>(
+        &'a self,
+        provider: &'a P,
+        k: &'a E::Key,
+    ) -> BoxFuture<'a, Result<Option<&'a E>>>
+    where
+        P: LoadOne<E>,
+    {
+        Box::pin(async move {
+            if let Some(row) = self.row(k) {
+                return Ok(row.value.as_ref());
+            }
+
+            let value = provider.load_one(k).await?;
+
+            Ok(self.insert_loaded(k.clone(), value).value.as_ref())
+        })
+    }
+
+    /// Indicates if the row of the key, or its absence, is already in the table.
+    pub fn is_loaded(&self, k: &E::Key) -> bool {
+        self.rows.read().contains_key(k)
+    }
+
+    /// The number of keys in the table, including the absent keys.
+    pub fn loaded_len(&self) -> usize {
+        self.rows.read().len()
+    }
+
+    /// Loads the keys that are not already in the table with a single [LoadMany] call.
+    pub fn preload<'a, P>(
+        &'a self,
+        provider: &'a P,
+        keys: &'a [E::Key],
+    ) -> BoxFuture<'a, Result<()>>
+    where
+        P: LoadMany<E>,
+    {
+        Box::pin(async move {
+            let missing = {
+                let rows = self.rows.read();
+
+                keys.iter()
+                    .filter(|k| !rows.contains_key(k))
+                    .cloned()
+                    .collect::<FxHashSet<_>>()
+                    .into_iter()
+                    .collect::<Vec<_>>()
+            };
+
+            if missing.is_empty() {
+                return Ok(());
+            }
+
+            let mut loaded = provider
+                .load_many(&missing)
+                .await?
+                .into_iter()
+                .collect::<FxHashMap<_, _>>();
+
+            for k in missing {
+                let value = loaded.remove(&k);
+                self.insert_loaded(k, value);
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Evicts the least recently used rows above `CAPACITY`.
+    fn evict(&mut self) {
+        let rows = self.rows.get_mut();
+
+        let Some(count) = rows.len().checked_sub(CAPACITY).filter(|c| *c > 0) else {
+            return;
+        };
+
+        let mut lru = rows
+            .values_mut()
+            .map(|r| (*r.lru.get_mut(), r.key.clone()))
+            .collect::<Vec<_>>();
+
+        lru.sort_unstable_by_key(|t| t.0);
+
+        for (_, key) in lru.into_iter().take(count) {
+            rows.remove(&key);
+        }
+    }
+
+    /// Extends the borrow of a row to the borrow of the table.
+    fn extend_borrow<'a>(&'a self, row: &LazyRow<E>) -> &'a LazyRow<E> {
+        let row: *const LazyRow<E> = row;
+
+        // SAFETY: the rows are boxed so they do not move when the map grows, and they are only
+        // removed or replaced through `&mut self`, which cannot happen while `self` is borrowed.
+        unsafe { &*row }
+    }
+
+    fn insert_loaded(&self, key: E::Key, value: Option<E>) -> &LazyRow<E> {
+        let mut rows = self.rows.write();
+
+        // a concurrent load may already have inserted the row, it is kept as it may be borrowed.
+        let row = rows.entry(key.clone()).or_insert_with(|| {
+            Box::new(LazyRow {
+                key,
+                lru: AtomicU64::new(0),
+                value,
+            })
+        });
+
+        row.lru.store(self.lru.fetch_add(1, Relaxed), Relaxed);
+        self.extend_borrow(row)
+    }
+
+    fn insert_mut(&mut self, key: E::Key, value: Option<E>) -> Option<Box<LazyRow<E>>> {
+        let row = Box::new(LazyRow {
+            key: key.clone(),
+            lru: AtomicU64::new(*self.lru.get_mut()),
+            value,
+        });
+
+        *self.lru.get_mut() += 1;
+        self.rows.get_mut().insert(key, row)
+    }
+
+    fn row(&self, k: &E::Key) -> Option<&LazyRow<E>> {
+        let rows = self.rows.read();
+        let row = rows.get(k)?;
+
+        row.lru.store(self.lru.fetch_add(1, Relaxed), Relaxed);
+        Some(self.extend_borrow(row))
+    }
+
+    fn update_metrics(&self)
+    where
+        E: CtxTypeInfo,
+    {
+        #[cfg(feature = "telemetry")]
+        crate::telemetry::update_storm_table_rows(self.loaded_len(), E::NAME);
+    }
+}
+
+impl<E, const CAPACITY: usize> AsRefAsync<LazyTable<E, CAPACITY>> for Ctx
+where
+    E: EntityAccessor<Tbl = LazyTable<E, CAPACITY>>,
+{
+    #[inline]
+    fn as_ref_async(&self) -> BoxFuture<'_, Result<&'_ LazyTable<E, CAPACITY>>> {
+        Box::pin(async move { Ok(LazyTable::__tbl_from(self)) })
+    }
+}
+
+impl<E: Entity, const CAPACITY: usize> AsRef<Self> for LazyTable<E, CAPACITY> {
+    #[inline]
+    fn as_ref(&self) -> &Self {
+        self
+    }
+}
+
+impl<E: EntityAccessor, const CAPACITY: usize> Clearable for LazyTable<E, CAPACITY> {
+    #[inline]
+    fn cleared() -> &'static ClearEvent {
+        E::cleared()
+    }
+}
+
+impl<E: Entity, const CAPACITY: usize> Default for LazyTable<E, CAPACITY> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E: Entity, const CAPACITY: usize> EntityOf for LazyTable<E, CAPACITY> {
+    type Entity = E;
+}
+
+impl<E, const CAPACITY: usize> Extend<(E::Key, E)> for LazyTable<E, CAPACITY>
+where
+    E: CtxTypeInfo + Entity,
+{
+    fn extend<T>(&mut self, iter: T)
+    where
+        T: IntoIterator<Item = (E::Key, E)>,
+    {
+        for (k, v) in iter {
+            self.insert_mut(k, Some(v));
+        }
+
+        self.evict();
+        self.update_metrics();
+    }
+}
+
+impl<E, const CAPACITY: usize> Gc for LazyTable<E, CAPACITY>
+where
+    E: CtxTypeInfo + Entity + Gc,
+{
+    const SUPPORT_GC: bool = true;
+
+    fn gc(&mut self) {
+        if E::SUPPORT_GC {
+            for row in self.rows.get_mut().values_mut() {
+                row.value.gc();
+            }
+        }
+
+        self.evict();
+        self.update_metrics();
+    }
+}
+
+impl<E: Entity, const CAPACITY: usize> Get<E> for LazyTable<E, CAPACITY> {
+    #[inline]
+    fn get(&self, k: &E::Key) -> Option<&E> {
+        self.row(k)?.value.as_ref()
+    }
+}
+
+impl<E: Entity, const CAPACITY: usize> GetMut<E> for LazyTable<E, CAPACITY> {
+    #[inline]
+    fn get_mut(&mut self, k: &E::Key) -> Option<&mut E> {
+        self.rows.get_mut().get_mut(k)?.value.as_mut()
+    }
+}
+
+impl<'a, E: Entity, const CAPACITY: usize> IntoIterator for &'a LazyTable<E, CAPACITY> {
+    type Item = (&'a E::Key, &'a E);
+    type IntoIter = IntoIter<(&'a E::Key, &'a E)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.rows
+            .read()
+            .values()
+            .filter_map(|row| {
+                let row = self.extend_borrow(row);
+                Some((&row.key, row.value.as_ref()?))
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+}
+
+impl<E: Entity, const CAPACITY: usize> NotifyTag for LazyTable<E, CAPACITY> {
+    #[inline]
+    fn notify_tag(&mut self) {
+        self.tag.notify()
+    }
+}
+
+impl<E: Entity, const CAPACITY: usize> RefIntoIterator for LazyTable<E, CAPACITY> {
+    type Item<'a> = (&'a E::Key, &'a E);
+    type Iter<'a> = IntoIter<(&'a E::Key, &'a E)>;
+
+    #[inline]
+    fn ref_iter(&self) -> Self::Iter<'_> {
+        self.into_iter()
+    }
+}
+
+impl<E: Entity, const CAPACITY: usize> Tag for LazyTable<E, CAPACITY> {
+    #[inline]
+    fn tag(&self) -> VersionTag {
+        self.tag
+    }
+}
+
+impl<E: EntityAccessor, const CAPACITY: usize> Touchable for LazyTable<E, CAPACITY> {
+    #[inline]
+    fn touched() -> &'static TouchedEvent {
+        E::touched()
+    }
+}
@@ -23,13 +23,20 @@ mod hash_table;
 pub mod indexing;
 mod is_defined;
 mod iterator_ext;
+mod lazy_table;
 mod len;
 mod logs;
 pub mod mem;
 mod one_to_many;
+mod partitioned_table;
+mod preload;
 pub mod prelude;
 pub mod provider;
 pub mod registry;
+mod retry;
+mod savepoint;
+mod serialized_logs;
+mod subscriptions;
 mod tag;
 #[cfg(feature = "telemetry")]
 #[doc(hidden)]
@@ -68,6 +75,7 @@ pub use get_mut::GetMut;
 pub use hash_table::HashTable;
 pub use is_defined::IsDefined;
 pub use iterator_ext::*;
+pub use lazy_table::LazyTable;
 pub use len::{Len, macro_check_max_len};
 pub use linkme;
 pub use logs::{LogOf, Logs};
@@ -76,9 +84,20 @@ pub use metrics;
 pub use once_cell::sync::OnceCell;
 pub use one_to_many::{OneToMany, OneToManyFromIter};
 pub use parking_lot;
+pub use partitioned_table::{PartitionedEntity, PartitionedTable};
+pub use preload::{Preload, PreloadTiming};
 pub use provider::ProviderContainer;
 pub use registry::set_date_provider;
+pub use retry::{RetryOptions, retry_transaction};
 pub use rustc_hash;
+pub use savepoint::{
+    __SnapshotClone, __SnapshotNoClone, __SnapshotProbe, __register_snapshot, Savepoint,
+};
+pub use serialized_logs::{
+    __LogCodecNoSerde, __LogCodecProbe, __LogCodecSerde, __LogNoReplay, __LogReplay,
+    __register_log_codec, LogCodec, ReplayFn, SerializedLogs,
+};
+pub use subscriptions::{Change, SUBSCRIPTION_CAPACITY};
 pub use tag::{NotifyTag, Tag};
 pub use tokio;
 pub use touchable::Touchable;
@@ -91,6 +110,7 @@ pub use vec_table::VecTable;
 pub use version_tag::{self, VersionTag};
 
 pub type BoxFuture<'a, T> = std::pin::Pin<Box<dyn std::future::Future<Output = T> + 'a + Send>>;
+pub type BoxStream<'a, T> = std::pin::Pin<Box<dyn futures::Stream<Item = T> + 'a + Send>>;
 pub type Result<T> = std::result::Result<T, Error>;
 
 pub const EV_CREATED: &str = "created";
@@ -99,11 +119,13 @@ pub const OBJ_TABLE: &str = "table";
 
 #[cfg(feature = "derive")]
 pub use storm_derive::{
-    Ctx, LocksAwait, NoopDelete, NoopLoad, NoopSave, flat_set_index, hash_flat_set_index, indexing,
-    one_index, register, single_set, tree_index,
+    Ctx, LocksAwait, MemDelete, MemLoad, MemSave, NoopDelete, NoopLoad, NoopSave, flat_set_index,
+    hash_flat_set_index, indexing, one_index, register, single_set, tree_index,
 };
 #[cfg(feature = "mssql")]
 pub use storm_derive::{MssqlDelete, MssqlLoad, MssqlSave};
+#[cfg(feature = "sqlite")]
+pub use storm_derive::{SqliteDelete, SqliteLoad, SqliteSave};
 
 #[macro_export]
 macro_rules! tri {
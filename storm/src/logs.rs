@@ -1,4 +1,7 @@
-use crate::{ApplyLog, Ctx, CtxExt, Entity, HashTable, OnceCell, Result, VecTable};
+use crate::{
+    ApplyLog, Ctx, CtxExt, Entity, HashTable, LazyTable, OnceCell, PartitionedEntity,
+    PartitionedTable, Result, VecTable,
+};
 use async_cell_lock::QueueRwLockQueueGuard;
 use extobj::{DynObj, Var, VarId};
 use rustc_hash::FxHashMap;
@@ -11,10 +14,18 @@ impl<E: Entity> LogOf for HashTable<E> {
     type Log = TableLog<E>;
 }
 
+impl<E: Entity, const CAPACITY: usize> LogOf for LazyTable<E, CAPACITY> {
+    type Log = TableLog<E>;
+}
+
 impl<T: LogOf> LogOf for OnceCell<T> {
     type Log = T::Log;
 }
 
+impl<E: PartitionedEntity> LogOf for PartitionedTable<E> {
+    type Log = TableLog<E>;
+}
+
 impl<E: Entity> LogOf for VecTable<E> {
     type Log = TableLog<E>;
 }
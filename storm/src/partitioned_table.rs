@@ -0,0 +1,427 @@
+use crate::{
+    AsRefAsync, BoxFuture, ClearEvent, Clearable, Ctx, CtxTypeInfo, EntityAccessor, EntityOf, Gc,
+    Get, GetMut, HashTable, Logs, NotifyTag, ProviderContainer, RefIntoIterator, Result, Tag,
+    Touchable, TouchedEvent,
+    logs::TableLog,
+    provider::{LoadAll, LoadOne},
+};
+use parking_lot::RwLock;
+use rustc_hash::FxHashMap;
+use std::{
+    any::type_name,
+    fmt::Debug,
+    hash::Hash,
+    sync::atomic::{AtomicBool, Ordering::Relaxed},
+    vec::IntoIter,
+};
+use tracing::error;
+use version_tag::VersionTag;
+
+/// An entity stored in a [PartitionedTable], each partition being loaded with its own filter.
+pub trait PartitionedEntity: EntityAccessor {
+    /// The scope of a partition, like a tenant id or a fiscal year.
+    type Partition: Clone + Debug + Eq + Hash + Send + Sync;
+
+    /// The filter used to load the rows of a partition.
+    type Filter: Send + Sync;
+
+    fn partition(&self) -> Self::Partition;
+    fn partition_filter(partition: &Self::Partition) -> Self::Filter;
+}
+
+/// A table split by a scope key, each partition being loaded on its first access with
+/// [Ctx::tbl_of_partition].
+///
+/// Each partition has its own version tag. The partitions that are not accessed between two
+/// [Ctx::gc] are dropped and loaded again on the next access.
+///
+/// Getting or iterating the table only looks at the loaded partitions. A transaction removing
+/// or upserting a row loads the partition of the row when needed.
+pub struct PartitionedTable<E: PartitionedEntity> {
+    partitions: RwLock<Partitions<E>>,
+    tag: VersionTag,
+}
+
+struct Partitions<E: PartitionedEntity> {
+    /// The partition of each row of the loaded partitions.
+    keys: FxHashMap<E::Key, E::Partition>,
+    loaded: FxHashMap<E::Partition, Box<Partition<E>>>,
+}
+
+impl<E: PartitionedEntity> Default for Partitions<E> {
+    fn default() -> Self {
+        Self {
+            keys: Default::default(),
+            loaded: Default::default(),
+        }
+    }
+}
+
+struct Partition<E: PartitionedEntity> {
+    tbl: HashTable<E>,
+    touched: AtomicBool,
+}
+
+impl<E: PartitionedEntity> Partition<E> {
+    fn new(tbl: HashTable<E>) -> Box<Self> {
+        Box::new(Self {
+            tbl,
+            touched: AtomicBool::new(true),
+        })
+    }
+}
+
+impl<E: PartitionedEntity> PartitionedTable<E> {
+    pub fn new() -> Self {
+        Self {
+            partitions: Default::default(),
+            tag: VersionTag::new(),
+        }
+    }
+
+    /// Used by the macro.
+    #[doc(hidden)]
+    pub fn __apply_log(ctx: &mut Ctx, logs: &mut Logs) -> bool
+    where
+        E: CtxTypeInfo + EntityAccessor<Tbl = Self>,
+    {
+        let Some(log) = logs.remove(E::tbl_var()) else {
+            return false;
+        };
+
+        if log.is_empty() {
+            return false;
+        }
+
+        let mut changes = ctx.subscriptions.changes::<E>();
+
+        let Some(tbl) = ctx.ctx_ext_obj.get_mut(E::tbl_var()).get_mut() else {
+            return false;
+        };
+
+        let partitions = tbl.partitions.get_mut();
+        let mut routed = FxHashMap::<E::Partition, TableLog<E>>::default();
+
+        for (k, new) in log {
+            // the rows of a partition that is not loaded are read on its first access.
+            let target = new
+                .as_ref()
+                .map(|e| e.partition())
+                .filter(|p| partitions.loaded.contains_key(p));
+
+            // a row moved to another partition is removed from its old partition.
+            if let Some(old) = partitions.keys.remove(&k)
+                && Some(&old) != target.as_ref()
+            {
+                routed.entry(old).or_default().insert(k.clone(), None);
+            }
+
+            if let Some(target) = target {
+                partitions.keys.insert(k.clone(), target.clone());
+                routed.entry(target).or_default().insert(k, new);
+            }
+        }
+
+        for (p, log) in routed {
+            if let Some(partition) = partitions.loaded.get_mut(&p) {
+                partition.tbl.apply(log, &mut changes);
+            }
+        }
+
+        tbl.update_metrics();
+        tbl.tag.notify();
+        ctx.subscriptions.defer(changes);
+        E::touched().call(ctx);
+
+        true
+    }
+
+    /// Used by the macro.
+    ///
+    /// A row found in no loaded partition is read with [LoadOne] and its partition is loaded.
+    #[doc(hidden)]
+    pub fn __entity_of<'a>(ctx: &'a Ctx, k: &'a E::Key) -> BoxFuture<'a, Result<Option<&'a E>>>
+    where
+        E: CtxTypeInfo + EntityAccessor<Tbl = Self>,
+        ProviderContainer: LoadAll<E, E::Filter, Vec<(E::Key, E)>> + LoadOne<E>,
+    {
+        Box::pin(async move {
+            let tbl = Self::__tbl_from(ctx);
+
+            if let Some(v) = tbl.get(k) {
+                return Ok(Some(v));
+            }
+
+            let Some(row) = ctx.provider.load_one(k).await? else {
+                return Ok(None);
+            };
+
+            tbl.partition(&ctx.provider, &row.partition()).await?;
+
+            Ok(tbl.get(k))
+        })
+    }
+
+    /// Used by the macro.
+    #[doc(hidden)]
+    pub fn __tbl_from(ctx: &Ctx) -> &Self
+    where
+        E: EntityAccessor<Tbl = Self>,
+    {
+        ctx.ctx_ext_obj.get(E::tbl_var()).get_or_init(Self::new)
+    }
+
+    /// Indicates if the partition is loaded.
+    pub fn is_loaded(&self, partition: &E::Partition) -> bool {
+        self.partitions.read().loaded.contains_key(partition)
+    }
+
+    /// The number of loaded partitions.
+    pub fn loaded_len(&self) -> usize {
+        self.partitions.read().loaded.len()
+    }
+
+    /// Gets a partition, loading it from the provider with [PartitionedEntity::partition_filter]
+    /// if it is not already loaded.
+    pub fn partition<'a>(
+        &'a self,
+        provider: &'a ProviderContainer,
+        partition: &'a E::Partition,
+    ) -> BoxFuture<'a, Result<&'a HashTable<E>>>
+    where
+        E: CtxTypeInfo,
+        ProviderContainer: LoadAll<E, E::Filter, Vec<(E::Key, E)>>,
+    {
+        Box::pin(async move {
+            if let Some(tbl) = self.loaded(partition) {
+                return Ok(tbl);
+            }
+
+            // the gate is removed from the provider once no load of the partition holds it.
+            let name = format!("{}[{partition:?}]", type_name::<E>());
+            let _gate = provider.gate(&name).await;
+
+            // the partition may be loaded when we gain access to the provider.
+            if let Some(tbl) = self.loaded(partition) {
+                return Ok(tbl);
+            }
+
+            let filter = E::partition_filter(partition);
+
+            let rows = provider.load_all(&filter).await.inspect_err(|e| {
+                error!({ error = %e, partition = ?partition }, "partition load failed");
+            })?;
+
+            let mut tbl = HashTable::new();
+
+            // the provider may return the rows of other partitions when it ignores the filter.
+            tbl.extend(
+                rows.into_iter()
+                    .filter(|(_, e)| e.partition() == *partition),
+            );
+
+            let tbl = self.insert_loaded(partition.clone(), tbl);
+            self.update_metrics();
+
+            Ok(tbl)
+        })
+    }
+
+    /// Extends the borrow of a partition to the borrow of the table.
+    fn extend_borrow<'a>(&'a self, partition: &Partition<E>) -> &'a HashTable<E> {
+        let tbl: *const HashTable<E> = &partition.tbl;
+
+        // SAFETY: the partitions are boxed so they do not move when the map grows, and they are
+        // only removed or replaced through `&mut self`, which cannot happen while `self` is borrowed.
+        unsafe { &*tbl }
+    }
+
+    fn insert_loaded(&self, partition: E::Partition, tbl: HashTable<E>) -> &HashTable<E> {
+        let mut partitions = self.partitions.write();
+        let Partitions { keys, loaded } = &mut *partitions;
+
+        // a concurrent load may already have inserted the partition, it is kept as it may be borrowed.
+        let partition = loaded.entry(partition).or_insert_with_key(|p| {
+            keys.extend(tbl.keys().map(|k| (k.clone(), p.clone())));
+            Partition::new(tbl)
+        });
+
+        self.extend_borrow(partition)
+    }
+
+    fn loaded(&self, partition: &E::Partition) -> Option<&HashTable<E>> {
+        let partitions = self.partitions.read();
+        let partition = partitions.loaded.get(partition)?;
+
+        partition.touched.store(true, Relaxed);
+        Some(self.extend_borrow(partition))
+    }
+
+    fn update_metrics(&self)
+    where
+        E: CtxTypeInfo,
+    {
+        #[cfg(feature = "telemetry")]
+        crate::telemetry::update_storm_table_rows(
+            self.partitions
+                .read()
+                .loaded
+                .values()
+                .map(|p| p.tbl.len())
+                .sum(),
+            E::NAME,
+        );
+    }
+}
+
+impl<E> AsRefAsync<PartitionedTable<E>> for Ctx
+where
+    E: PartitionedEntity<Tbl = PartitionedTable<E>>,
+{
+    #[inline]
+    fn as_ref_async(&self) -> BoxFuture<'_, Result<&'_ PartitionedTable<E>>> {
+        Box::pin(async move { Ok(PartitionedTable::__tbl_from(self)) })
+    }
+}
+
+impl<E: PartitionedEntity> AsRef<Self> for PartitionedTable<E> {
+    #[inline]
+    fn as_ref(&self) -> &Self {
+        self
+    }
+}
+
+impl<E: PartitionedEntity> Clearable for PartitionedTable<E> {
+    #[inline]
+    fn cleared() -> &'static ClearEvent {
+        E::cleared()
+    }
+}
+
+impl<E: PartitionedEntity> Default for PartitionedTable<E> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E: PartitionedEntity> EntityOf for PartitionedTable<E> {
+    type Entity = E;
+}
+
+impl<E> Extend<(E::Key, E)> for PartitionedTable<E>
+where
+    E: CtxTypeInfo + PartitionedEntity,
+{
+    fn extend<T>(&mut self, iter: T)
+    where
+        T: IntoIterator<Item = (E::Key, E)>,
+    {
+        let partitions = self.partitions.get_mut();
+
+        for (k, v) in iter {
+            let partition = v.partition();
+
+            partitions.keys.insert(k.clone(), partition.clone());
+            partitions
+                .loaded
+                .entry(partition)
+                .or_insert_with(|| Partition::new(HashTable::new()))
+                .tbl
+                .extend([(k, v)]);
+        }
+
+        self.update_metrics();
+    }
+}
+
+impl<E> Gc for PartitionedTable<E>
+where
+    E: CtxTypeInfo + PartitionedEntity + Gc,
+{
+    const SUPPORT_GC: bool = true;
+
+    fn gc(&mut self) {
+        let Partitions { keys, loaded } = self.partitions.get_mut();
+        let len = loaded.len();
+
+        loaded.retain(|_, p| std::mem::replace(p.touched.get_mut(), false));
+
+        if loaded.len() < len {
+            keys.retain(|_, p| loaded.contains_key(p));
+        }
+
+        if E::SUPPORT_GC {
+            for partition in loaded.values_mut() {
+                partition.tbl.gc();
+            }
+        }
+
+        self.update_metrics();
+    }
+}
+
+impl<E: PartitionedEntity> Get<E> for PartitionedTable<E> {
+    fn get(&self, k: &E::Key) -> Option<&E> {
+        let partitions = self.partitions.read();
+        let partition = partitions.loaded.get(partitions.keys.get(k)?)?;
+
+        partition.touched.store(true, Relaxed);
+        self.extend_borrow(partition).get(k)
+    }
+}
+
+impl<E: PartitionedEntity> GetMut<E> for PartitionedTable<E> {
+    fn get_mut(&mut self, k: &E::Key) -> Option<&mut E> {
+        let partitions = self.partitions.get_mut();
+        let partition = partitions.loaded.get_mut(partitions.keys.get(k)?)?;
+
+        partition.tbl.get_mut(k)
+    }
+}
+
+impl<'a, E: PartitionedEntity> IntoIterator for &'a PartitionedTable<E> {
+    type Item = (&'a E::Key, &'a E);
+    type IntoIter = IntoIter<(&'a E::Key, &'a E)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.partitions
+            .read()
+            .loaded
+            .values()
+            .flat_map(|partition| self.extend_borrow(partition).iter())
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+}
+
+impl<E: PartitionedEntity> NotifyTag for PartitionedTable<E> {
+    #[inline]
+    fn notify_tag(&mut self) {
+        self.tag.notify()
+    }
+}
+
+impl<E: PartitionedEntity> RefIntoIterator for PartitionedTable<E> {
+    type Item<'a> = (&'a E::Key, &'a E);
+    type Iter<'a> = IntoIter<(&'a E::Key, &'a E)>;
+
+    #[inline]
+    fn ref_iter(&self) -> Self::Iter<'_> {
+        self.into_iter()
+    }
+}
+
+impl<E: PartitionedEntity> Tag for PartitionedTable<E> {
+    #[inline]
+    fn tag(&self) -> VersionTag {
+        self.tag
+    }
+}
+
+impl<E: PartitionedEntity> Touchable for PartitionedTable<E> {
+    #[inline]
+    fn touched() -> &'static TouchedEvent {
+        E::touched()
+    }
+}
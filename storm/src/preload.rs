@@ -0,0 +1,58 @@
+use crate::{AsRefAsync, BoxFuture, Ctx, CtxTypeInfo, EntityAccessor, Result};
+use std::time::{Duration, Instant};
+
+/// The time taken to load a table during a [Ctx::preload].
+#[derive(Clone, Debug)]
+pub struct PreloadTiming {
+    pub name: &'static str,
+    pub elapsed: Duration,
+}
+
+/// A set of tables that can be loaded concurrently by [Ctx::preload].
+///
+/// It is implemented for entities and tuples of entities.
+pub trait Preload {
+    fn preload<'a>(ctx: &'a Ctx, futures: &mut Vec<BoxFuture<'a, Result<PreloadTiming>>>);
+}
+
+impl<E> Preload for E
+where
+    E: EntityAccessor + CtxTypeInfo,
+    Ctx: AsRefAsync<E::Tbl>,
+{
+    fn preload<'a>(ctx: &'a Ctx, futures: &mut Vec<BoxFuture<'a, Result<PreloadTiming>>>) {
+        futures.push(Box::pin(async move {
+            let instant = Instant::now();
+
+            ctx.tbl_of::<E>().await?;
+
+            Ok(PreloadTiming {
+                name: E::NAME,
+                elapsed: instant.elapsed(),
+            })
+        }));
+    }
+}
+
+macro_rules! preload_tuple {
+    ($($t:ident),+) => {
+        impl<$($t: Preload),+> Preload for ($($t,)+) {
+            fn preload<'a>(ctx: &'a Ctx, futures: &mut Vec<BoxFuture<'a, Result<PreloadTiming>>>) {
+                $($t::preload(ctx, futures);)+
+            }
+        }
+    };
+}
+
+preload_tuple!(A);
+preload_tuple!(A, B);
+preload_tuple!(A, B, C);
+preload_tuple!(A, B, C, D);
+preload_tuple!(A, B, C, D, E);
+preload_tuple!(A, B, C, D, E, F);
+preload_tuple!(A, B, C, D, E, F, G);
+preload_tuple!(A, B, C, D, E, F, G, H);
+preload_tuple!(A, B, C, D, E, F, G, H, I);
+preload_tuple!(A, B, C, D, E, F, G, H, I, J);
+preload_tuple!(A, B, C, D, E, F, G, H, I, J, K);
+preload_tuple!(A, B, C, D, E, F, G, H, I, J, K, L);
@@ -1,6 +1,6 @@
 pub use crate::{
-    ApplyLog, AsyncOnceCell, Ctx, Entity, Get, HashTable, ProviderContainer, QueueRwLock, Tag,
-    Transaction, VecTable,
+    ApplyLog, AsyncOnceCell, Ctx, Entity, Get, HashTable, LazyTable, PartitionedEntity,
+    PartitionedTable, ProviderContainer, QueueRwLock, Tag, Transaction, VecTable,
 };
 
 #[cfg(feature = "derive")]
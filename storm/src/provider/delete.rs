@@ -2,6 +2,18 @@ use crate::{BoxFuture, Entity, Result};
 
 pub trait Delete<E: Entity>: Send + Sync {
     fn delete<'a>(&'a self, k: &'a E::Key) -> BoxFuture<'a, Result<()>>;
+
+    /// Deletes many rows at once. The default implementation calls [delete](Delete::delete)
+    /// for each key, a provider can override it to delete the rows in fewer round trips.
+    fn delete_many<'a>(&'a self, keys: &'a [E::Key]) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            for k in keys {
+                self.delete(k).await?;
+            }
+
+            Ok(())
+        })
+    }
 }
 
 impl<E, T> Delete<E> for &T
@@ -13,4 +25,8 @@ where
     fn delete<'a>(&'a self, k: &'a E::Key) -> BoxFuture<'a, Result<()>> {
         (**self).delete(k)
     }
+
+    fn delete_many<'a>(&'a self, keys: &'a [E::Key]) -> BoxFuture<'a, Result<()>> {
+        (**self).delete_many(keys)
+    }
 }
@@ -0,0 +1,36 @@
+use super::{LoadArgs, LoadOne};
+use crate::{BoxFuture, Entity, Result};
+
+/// The entities loaded by [LoadMany] with their keys.
+pub type LoadManyRows<E> = Vec<(<E as Entity>::Key, E)>;
+
+/// Loads the entities of many keys, the keys that are not found are not returned.
+///
+/// The default implementation loads the keys one at a time.
+pub trait LoadMany<E: Entity>: LoadOne<E> {
+    fn load_many_with_args<'a>(
+        &'a self,
+        keys: &'a [E::Key],
+        args: LoadArgs,
+    ) -> BoxFuture<'a, Result<LoadManyRows<E>>> {
+        Box::pin(async move {
+            let mut vec = Vec::with_capacity(keys.len());
+
+            for k in keys {
+                let args = LoadArgs {
+                    use_transaction: args.use_transaction,
+                };
+
+                if let Some(v) = self.load_one_with_args(k, args).await? {
+                    vec.push((k.clone(), v));
+                }
+            }
+
+            Ok(vec)
+        })
+    }
+
+    fn load_many<'a>(&'a self, keys: &'a [E::Key]) -> BoxFuture<'a, Result<LoadManyRows<E>>> {
+        self.load_many_with_args(keys, LoadArgs::default())
+    }
+}
@@ -0,0 +1,31 @@
+use super::LoadArgs;
+use crate::{BoxStream, Entity, Result};
+
+/// Loads the rows one at a time instead of collecting them like [LoadAll](super::LoadAll),
+/// to process the tables too large to fit in memory.
+pub trait LoadStream<E: Entity, FILTER: Send + Sync>: Send + Sync {
+    fn load_stream_with_args<'a>(
+        &'a self,
+        filter: &'a FILTER,
+        args: LoadArgs,
+    ) -> BoxStream<'a, Result<(E::Key, E)>>;
+
+    fn load_stream<'a>(&'a self, filter: &'a FILTER) -> BoxStream<'a, Result<(E::Key, E)>> {
+        self.load_stream_with_args(filter, LoadArgs::default())
+    }
+}
+
+impl<E, FILTER, P> LoadStream<E, FILTER> for &P
+where
+    E: Entity,
+    FILTER: Send + Sync,
+    P: LoadStream<E, FILTER>,
+{
+    fn load_stream_with_args<'a>(
+        &'a self,
+        filter: &'a FILTER,
+        args: LoadArgs,
+    ) -> BoxStream<'a, Result<(E::Key, E)>> {
+        (**self).load_stream_with_args(filter, args)
+    }
+}
@@ -0,0 +1,53 @@
+use super::{
+    MemProvider, ProviderFactory,
+    mem_provider::{KeyGenerator, MemStore},
+};
+use crate::{BoxFuture, Entity, IsDefined, Result};
+use std::{any::TypeId, sync::Arc};
+
+/// Creates [MemProvider] sharing the same rows.
+///
+/// ```
+/// use storm::provider::{MemFactory, ProviderContainer};
+///
+/// let mut provider = ProviderContainer::new();
+/// provider.register("", MemFactory::new());
+/// ```
+#[derive(Clone, Default)]
+pub struct MemFactory(Arc<MemStore>);
+
+impl MemFactory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a generator used by [UpsertMut](super::UpsertMut) to assign the key of
+    /// an entity when it is not defined, the same way an identity column would.
+    pub fn with_key_generator<E, F>(self, f: F) -> Self
+    where
+        E: Entity,
+        E::Key: IsDefined,
+        F: Fn() -> E::Key + Send + Sync + 'static,
+    {
+        let generator: KeyGenerator<E> = Box::new(move |k| {
+            if !k.is_defined() {
+                *k = f();
+            }
+        });
+
+        self.0
+            .key_generators
+            .write()
+            .insert(TypeId::of::<E>(), Box::new(generator));
+
+        self
+    }
+}
+
+impl ProviderFactory for MemFactory {
+    type Provider = MemProvider;
+
+    fn create_provider(&self) -> BoxFuture<'_, Result<Self::Provider>> {
+        Box::pin(async move { Ok(MemProvider::new(Arc::clone(&self.0))) })
+    }
+}
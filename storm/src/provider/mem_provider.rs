@@ -0,0 +1,287 @@
+use super::{
+    Delete, LoadAll, LoadArgs, LoadMany, LoadManyRows, LoadOne, Provider, Upsert, UpsertMut,
+};
+use crate::{BoxFuture, Entity, Result};
+use parking_lot::{Mutex, RwLock};
+use rustc_hash::FxHashMap;
+use std::{
+    any::{Any, TypeId},
+    sync::Arc,
+};
+
+/// Committed rows, by entity type. Each value is a [MemTable].
+type Tables = FxHashMap<TypeId, Box<dyn Any + Send + Sync>>;
+
+type MemTable<E> = FxHashMap<<E as Entity>::Key, E>;
+
+/// The uncommitted changes, by entity type.
+type Pendings = FxHashMap<TypeId, Box<dyn PendingTable>>;
+
+/// A key generator, by entity type. Each value is a [KeyGenerator].
+pub(super) type KeyGenerators = FxHashMap<TypeId, Box<dyn Any + Send + Sync>>;
+
+pub(super) type KeyGenerator<E> = Box<dyn Fn(&mut <E as Entity>::Key) + Send + Sync>;
+
+/// The storage shared between the [MemFactory](super::MemFactory) and all the
+/// providers it creates, which means the rows survive the garbage collection
+/// of the [ProviderContainer](super::ProviderContainer).
+#[derive(Default)]
+pub(super) struct MemStore {
+    pub(super) key_generators: RwLock<KeyGenerators>,
+    tables: Mutex<Tables>,
+}
+
+/// An in-memory provider that keeps the rows of each entity type.
+///
+/// Writes are kept pending until [Provider::commit] is called and
+/// are discarded on [Provider::cancel], exactly like a database transaction.
+/// Savepoints keep a copy of the pending writes.
+///
+/// This provider is intended for tests that must run without a database.
+pub struct MemProvider {
+    pending: Mutex<Pendings>,
+    savepoints: Mutex<Vec<(Box<str>, Pendings)>>,
+    store: Arc<MemStore>,
+}
+
+impl MemProvider {
+    pub(super) fn new(store: Arc<MemStore>) -> Self {
+        Self {
+            pending: Default::default(),
+            savepoints: Default::default(),
+            store,
+        }
+    }
+
+    fn load<E, C>(&self, args: &LoadArgs, mut filter: impl FnMut(&E::Key) -> bool) -> C
+    where
+        C: Default + Extend<(E::Key, E)>,
+        E: Entity + Clone,
+    {
+        let pending = args.use_transaction.then(|| self.pending.lock());
+
+        let pending = pending
+            .as_ref()
+            .and_then(|p| p.get(&TypeId::of::<E>()))
+            .and_then(|p| (&**p as &dyn Any).downcast_ref::<Pending<E>>());
+
+        let tables = self.store.tables.lock();
+        let mut c = C::default();
+
+        if let Some(table) = tables
+            .get(&TypeId::of::<E>())
+            .and_then(|t| t.downcast_ref::<MemTable<E>>())
+        {
+            c.extend(
+                table
+                    .iter()
+                    .filter(|(k, _)| filter(k) && pending.is_none_or(|p| !p.0.contains_key(*k)))
+                    .map(|(k, v)| (k.clone(), v.clone())),
+            );
+        }
+
+        if let Some(pending) = pending {
+            c.extend(pending.0.iter().filter_map(|(k, v)| match v {
+                Some(v) if filter(k) => Some((k.clone(), v.clone())),
+                _ => None,
+            }));
+        }
+
+        c
+    }
+
+    fn write<E: Entity + Clone>(&self, k: E::Key, v: Option<E>) {
+        let mut pending = self.pending.lock();
+
+        let table = pending
+            .entry(TypeId::of::<E>())
+            .or_insert_with(|| Box::new(Pending::<E>(FxHashMap::default())));
+
+        if let Some(table) = (&mut **table as &mut dyn Any).downcast_mut::<Pending<E>>() {
+            table.0.insert(k, v);
+        }
+    }
+}
+
+impl Provider for MemProvider {
+    fn cancel(&self) {
+        self.pending.lock().clear();
+        self.savepoints.lock().clear();
+    }
+
+    fn commit(&self) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move {
+            let pending = std::mem::take(&mut *self.pending.lock());
+            self.savepoints.lock().clear();
+
+            let mut tables = self.store.tables.lock();
+
+            for table in pending.into_values() {
+                table.apply(&mut tables);
+            }
+
+            Ok(())
+        })
+    }
+
+    fn rollback_to<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let mut pending = self.pending.lock();
+            let mut savepoints = self.savepoints.lock();
+
+            match savepoints.iter().rposition(|(n, _)| &**n == name) {
+                Some(index) => {
+                    savepoints.truncate(index + 1);
+
+                    if let Some((_, snapshot)) = savepoints.last() {
+                        *pending = clone_pendings(snapshot);
+                    }
+                }
+                None => {
+                    pending.clear();
+                    savepoints.clear();
+                }
+            }
+
+            Ok(())
+        })
+    }
+
+    fn savepoint<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let snapshot = clone_pendings(&self.pending.lock());
+            self.savepoints.lock().push((name.into(), snapshot));
+            Ok(())
+        })
+    }
+}
+
+impl<C, E> LoadAll<E, (), C> for MemProvider
+where
+    C: Default + Extend<(E::Key, E)> + Send,
+    E: Entity + Clone,
+{
+    fn load_all_with_args<'a>(
+        &'a self,
+        _filter: &'a (),
+        args: LoadArgs,
+    ) -> BoxFuture<'a, Result<C>> {
+        Box::pin(async move { Ok(self.load(&args, |_| true)) })
+    }
+}
+
+impl<E> LoadOne<E> for MemProvider
+where
+    E: Entity + Clone,
+{
+    fn load_one_with_args<'a>(
+        &'a self,
+        k: &'a E::Key,
+        args: LoadArgs,
+    ) -> BoxFuture<'a, Result<Option<E>>> {
+        Box::pin(async move {
+            let v: Vec<(E::Key, E)> = self.load(&args, |key| key == k);
+            Ok(v.into_iter().next().map(|t| t.1))
+        })
+    }
+}
+
+impl<E> LoadMany<E> for MemProvider
+where
+    E: Entity + Clone,
+{
+    fn load_many_with_args<'a>(
+        &'a self,
+        keys: &'a [E::Key],
+        args: LoadArgs,
+    ) -> BoxFuture<'a, Result<LoadManyRows<E>>> {
+        Box::pin(async move { Ok(self.load(&args, |key| keys.contains(key))) })
+    }
+}
+
+impl<E> Upsert<E> for MemProvider
+where
+    E: Entity + Clone,
+{
+    fn upsert<'a>(&'a self, k: &'a E::Key, v: &'a E) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            self.write(k.clone(), Some(v.clone()));
+            Ok(())
+        })
+    }
+}
+
+/// Assigns a key with the generator registered on the [MemFactory](super::MemFactory)
+/// for this entity type, if any, before upserting the entity.
+impl<E> UpsertMut<E> for MemProvider
+where
+    E: Entity + Clone,
+{
+    fn upsert_mut<'a>(&'a self, k: &'a mut E::Key, v: &'a mut E) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            if let Some(generator) = self
+                .store
+                .key_generators
+                .read()
+                .get(&TypeId::of::<E>())
+                .and_then(|g| g.downcast_ref::<KeyGenerator<E>>())
+            {
+                generator(k);
+            }
+
+            self.write(k.clone(), Some(v.clone()));
+            Ok(())
+        })
+    }
+}
+
+impl<E> Delete<E> for MemProvider
+where
+    E: Entity + Clone,
+{
+    fn delete<'a>(&'a self, k: &'a E::Key) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            self.write::<E>(k.clone(), None);
+            Ok(())
+        })
+    }
+}
+
+/// The uncommitted changes of an entity type.
+trait PendingTable: Any + Send + Sync {
+    fn apply(self: Box<Self>, tables: &mut Tables);
+    fn clone_box(&self) -> Box<dyn PendingTable>;
+}
+
+/// A `None` value is a deleted row.
+struct Pending<E: Entity>(FxHashMap<E::Key, Option<E>>);
+
+impl<E: Entity + Clone> PendingTable for Pending<E> {
+    fn apply(self: Box<Self>, tables: &mut Tables) {
+        let table = tables
+            .entry(TypeId::of::<E>())
+            .or_insert_with(|| Box::new(MemTable::<E>::default()));
+
+        let Some(table) = table.downcast_mut::<MemTable<E>>() else {
+            return;
+        };
+
+        for (k, v) in self.0 {
+            match v {
+                Some(v) => table.insert(k, v),
+                None => table.remove(&k),
+            };
+        }
+    }
+
+    fn clone_box(&self) -> Box<dyn PendingTable> {
+        Box::new(Self(self.0.clone()))
+    }
+}
+
+fn clone_pendings(pendings: &Pendings) -> Pendings {
+    pendings
+        .iter()
+        .map(|(id, table)| (*id, table.clone_box()))
+        .collect()
+}
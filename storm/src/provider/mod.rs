@@ -1,20 +1,30 @@
 mod cast_provider;
 mod delete;
 mod load_all;
+mod load_many;
 mod load_one;
+mod load_stream;
+mod mem_factory;
+mod mem_provider;
 #[allow(clippy::module_inception)]
 mod provider;
 mod provider_container;
 mod provider_factory;
+mod recovery_log;
 mod transaction_provider;
 mod upsert;
 
 use cast_provider::CastProvider;
 pub use delete::Delete;
 pub use load_all::*;
+pub use load_many::{LoadMany, LoadManyRows};
 pub use load_one::*;
-pub use provider::Provider;
+pub use load_stream::LoadStream;
+pub use mem_factory::MemFactory;
+pub use mem_provider::MemProvider;
+pub use provider::{CommitPhase, Provider};
 pub use provider_container::ProviderContainer;
 pub use provider_factory::ProviderFactory;
+pub use recovery_log::{CommitRecord, RecoveryLog};
 pub use transaction_provider::TransactionProvider;
 pub use upsert::*;
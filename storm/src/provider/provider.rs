@@ -1,7 +1,47 @@
-use crate::{BoxFuture, Result};
-use std::any::Any;
+use crate::{BoxFuture, Error, Result};
+use std::{
+    any::Any,
+    fmt::{self, Display},
+};
 
 pub trait Provider: Any + Send + Sync {
     fn cancel(&self);
     fn commit(&self) -> BoxFuture<'_, Result<()>>;
+
+    /// The first phase of a commit across multiple providers. A provider must return an error
+    /// if it is unable to commit its transaction, in which case all the providers are cancelled.
+    fn prepare(&self) -> BoxFuture<'_, Result<()>> {
+        Box::pin(std::future::ready(Ok(())))
+    }
+
+    /// Rolls back the work done since the savepoint `name` was created, the transaction stays open.
+    ///
+    /// A provider that did not begin its transaction before the savepoint was created must
+    /// roll back all its work.
+    #[allow(unused_variables)]
+    fn rollback_to<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<()>> {
+        Box::pin(std::future::ready(Err(Error::SavepointNotSupported)))
+    }
+
+    /// Creates a named savepoint in the current transaction, if any.
+    #[allow(unused_variables)]
+    fn savepoint<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<()>> {
+        Box::pin(std::future::ready(Err(Error::SavepointNotSupported)))
+    }
+}
+
+/// The phase of a commit across multiple providers.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CommitPhase {
+    Prepare,
+    Commit,
+}
+
+impl Display for CommitPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Prepare => f.write_str("prepare"),
+            Self::Commit => f.write_str("commit"),
+        }
+    }
 }
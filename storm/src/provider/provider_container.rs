@@ -1,17 +1,33 @@
-use super::{CastProvider, Provider, ProviderFactory, TransactionProvider};
-use crate::{BoxFuture, Error, Result};
+use super::{
+    CastProvider, LoadArgs, LoadMany, Provider, ProviderFactory, RecoveryLog, TransactionProvider,
+};
+use crate::{BoxFuture, Entity, Error, Result};
+use rustc_hash::{FxHashMap, FxHashSet};
 use std::{
-    any::TypeId,
+    any::{Any, TypeId},
+    future::poll_fn,
     marker::PhantomData,
-    sync::atomic::{AtomicU64, Ordering::Relaxed},
+    mem::take,
+    slice,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering::Relaxed},
+    },
+    task::Poll,
     time::Instant,
 };
-use tokio::sync::{Mutex, MutexGuard, OnceCell as AsyncOnceCell};
+use tokio::sync::{Mutex, OnceCell as AsyncOnceCell, OwnedMutexGuard, oneshot};
 use tracing::{error, warn};
 
 /// Last recent use counter
 type Lru = AtomicU64;
 
+/// The keys waiting to be loaded together by [ProviderContainer::load_one_coalesced].
+type Pending<E> = Vec<(<E as Entity>::Key, oneshot::Sender<Option<E>>)>;
+
+/// The pending loads by entity type and `use_transaction`.
+type PendingMap = FxHashMap<(TypeId, bool), Box<dyn Any + Send>>;
+
 /// A trait that wrap the ProviderFactory to be able to use it in a Box<Any> trait object context.
 trait AnyFactory: Send + Sync + 'static {
     fn create(&self) -> BoxFuture<'_, Result<CastProvider>>;
@@ -47,10 +63,15 @@ where
 ///
 /// A database provider can be named and have a type.
 pub struct ProviderContainer {
+    coalesce: parking_lot::Mutex<PendingMap>,
+
+    /// A gate by table or index name, so independent loads can run concurrently. A gate is
+    /// removed once no load holds or waits on it.
+    gates: parking_lot::Mutex<FxHashMap<Box<str>, Arc<Mutex<()>>>>,
     last_gc: u64,
-    lock: Mutex<()>,
     lru: Lru,
     records: Vec<Rec>,
+    recovery_log: Option<Box<dyn RecoveryLog>>,
 }
 
 impl ProviderContainer {
@@ -71,9 +92,25 @@ impl ProviderContainer {
 
     #[doc(hidden)]
     /// Internal. Used by macro.
+    ///
+    /// Only the loads sharing the same name are serialized.
     pub async fn gate<'a>(&'a self, name: &'a str) -> ProviderGuard<'a> {
         let instant = Instant::now();
-        let guard = self.lock.lock().await;
+
+        let gate = {
+            let mut gates = self.gates.lock();
+
+            match gates.get(name) {
+                Some(gate) => Arc::clone(gate),
+                None => {
+                    let gate = Arc::new(Mutex::new(()));
+                    gates.insert(name.into(), Arc::clone(&gate));
+                    gate
+                }
+            }
+        };
+
+        let guard = gate.lock_owned().await;
 
         let elapsed = instant.elapsed().as_millis();
 
@@ -86,8 +123,9 @@ impl ProviderContainer {
         }
 
         ProviderGuard {
+            container: self,
+            guard,
             instant: Instant::now(),
-            _guard: guard,
             name,
         }
     }
@@ -112,6 +150,99 @@ impl ProviderContainer {
         }
     }
 
+    /// Loads an entity, the concurrent loads of the same entity type issued during the same
+    /// tick are merged in a single [LoadMany] query.
+    ///
+    /// A key requested more than once in the same batch, or a batch that failed, is loaded
+    /// again on its own so each caller gets its own result or error.
+    pub fn load_one_coalesced<'a, E>(
+        &'a self,
+        k: &'a E::Key,
+        args: LoadArgs,
+    ) -> BoxFuture<'a, Result<Option<E>>>
+    where
+        E: Entity,
+        Self: LoadMany<E>,
+    {
+        Box::pin(async move {
+            let id = (TypeId::of::<E>(), args.use_transaction);
+            let (tx, rx) = oneshot::channel();
+
+            let is_leader = {
+                let mut coalesce = self.coalesce.lock();
+
+                match coalesce
+                    .get_mut(&id)
+                    .and_then(|p| p.downcast_mut::<Pending<E>>())
+                {
+                    Some(pending) => {
+                        pending.push((k.clone(), tx));
+                        false
+                    }
+                    None => {
+                        let pending: Pending<E> = vec![(k.clone(), tx)];
+                        coalesce.insert(id, Box::new(pending));
+                        true
+                    }
+                }
+            };
+
+            if is_leader {
+                // removes the batch if this future is dropped, the other callers then load alone.
+                let guard = PendingGuard {
+                    container: self,
+                    id,
+                };
+
+                yield_now().await;
+
+                let pending = guard.take::<E>();
+                self.load_pending(pending, &args).await;
+            }
+
+            match rx.await {
+                Ok(v) => Ok(v),
+                Err(_) => {
+                    let mut vec = self.load_many_with_args(slice::from_ref(k), args).await?;
+                    Ok(vec.pop().map(|t| t.1))
+                }
+            }
+        })
+    }
+
+    async fn load_pending<E>(&self, pending: Pending<E>, args: &LoadArgs)
+    where
+        E: Entity,
+        Self: LoadMany<E>,
+    {
+        let keys = pending.iter().map(|t| t.0.clone()).collect::<Vec<_>>();
+
+        let args = LoadArgs {
+            use_transaction: args.use_transaction,
+        };
+
+        // on error, the senders are dropped and each caller loads its key alone.
+        let Ok(rows) = self.load_many_with_args(&keys, args).await else {
+            return;
+        };
+
+        let mut rows = rows.into_iter().collect::<FxHashMap<_, _>>();
+        let mut sent = FxHashSet::default();
+
+        for (k, tx) in pending {
+            match rows.remove(&k) {
+                Some(v) => {
+                    let _ = tx.send(Some(v));
+                    sent.insert(k);
+                }
+                None if sent.contains(&k) => {}
+                None => {
+                    let _ = tx.send(None);
+                }
+            }
+        }
+    }
+
     /// Gets or creates a database provider that have been previously registered with
     /// the `register` method.
     pub fn provide<'a, P: Provider>(&'a self, name: &'a str) -> BoxFuture<'a, Result<&'a P>> {
@@ -127,10 +258,10 @@ impl ProviderContainer {
         })
     }
 
-    pub(super) fn providers(&self) -> impl Iterator<Item = &'_ dyn Provider> {
+    pub(super) fn providers(&self) -> impl Iterator<Item = (&'_ str, &'_ dyn Provider)> {
         self.records
             .iter()
-            .filter_map(|r| Some(r.get()?.provider()))
+            .filter_map(|r| Some((&*r.name, r.get()?.provider())))
     }
 
     /// Register a provider factory that creates provider on demand. A provider can be named.
@@ -149,6 +280,53 @@ impl ProviderContainer {
         }
     }
 
+    pub(super) fn recovery_log(&self) -> Option<&dyn RecoveryLog> {
+        self.recovery_log.as_deref()
+    }
+
+    /// Completes the commits left pending in the [RecoveryLog], to be called at startup before
+    /// any transaction. Returns the number of commits recovered.
+    ///
+    /// The logs of the pending providers of each record are replayed in a transaction that
+    /// is not written to the recovery log, then the record is finished with all its providers
+    /// committed. A failure leaves the record pending, so the recovery can be run again.
+    pub async fn recover(&self) -> Result<usize> {
+        let Some(log) = self.recovery_log() else {
+            return Ok(0);
+        };
+
+        let records = log.pending().await?;
+        let count = records.len();
+
+        for mut record in records {
+            let trx = self.transaction();
+
+            for name in &record.pending {
+                if let Some(logs) = record.logs.get(name) {
+                    logs.replay(&trx).await?;
+                }
+            }
+
+            trx.commit_record(None).await?;
+
+            let pending = take(&mut record.pending);
+            record.committed.extend(pending);
+            log.finished(&record).await?;
+        }
+
+        Ok(count)
+    }
+
+    /// Sets the log persisting the commits across multiple providers, to be able to recover
+    /// a transaction partially committed with [recover](Self::recover).
+    ///
+    /// The entities of the transactions must implement `Serialize`, `Deserialize`,
+    /// [Upsert](super::Upsert) and [Delete](super::Delete) on the [TransactionProvider] so their
+    /// logs can be replayed. The logs are split by the `provider` attribute of the entity.
+    pub fn set_recovery_log<L: RecoveryLog>(&mut self, log: L) {
+        self.recovery_log = Some(Box::new(log));
+    }
+
     pub fn transaction(&self) -> TransactionProvider<'_> {
         TransactionProvider(self)
     }
@@ -157,14 +335,43 @@ impl ProviderContainer {
 impl Default for ProviderContainer {
     fn default() -> Self {
         Self {
+            coalesce: Default::default(),
+            gates: Default::default(),
             last_gc: 0,
-            lock: Mutex::new(()),
             lru: AtomicU64::new(1), // starting at 1 because the garbage collector start at 0.
             records: Vec::new(),
+            recovery_log: None,
         }
     }
 }
 
+struct PendingGuard<'a> {
+    container: &'a ProviderContainer,
+    id: (TypeId, bool),
+}
+
+impl PendingGuard<'_> {
+    fn take<E: Entity>(self) -> Pending<E> {
+        let pending = self
+            .container
+            .coalesce
+            .lock()
+            .remove(&self.id)
+            .and_then(|p| p.downcast().ok())
+            .map_or_else(Vec::new, |p| *p);
+
+        // a new batch may already be waiting under the same id.
+        std::mem::forget(self);
+        pending
+    }
+}
+
+impl Drop for PendingGuard<'_> {
+    fn drop(&mut self) {
+        self.container.coalesce.lock().remove(&self.id);
+    }
+}
+
 struct ProviderRec {
     lru: Lru,
     cast_provider: CastProvider,
@@ -205,6 +412,22 @@ impl Rec {
     }
 }
 
+/// Lets the other loads of the same tick join the batch.
+async fn yield_now() {
+    let mut yielded = false;
+
+    poll_fn(|cx| {
+        if yielded {
+            Poll::Ready(())
+        } else {
+            yielded = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    })
+    .await
+}
+
 fn rec_key(rec: &Rec) -> (TypeId, &str) {
     (rec.type_id, &rec.name)
 }
@@ -212,13 +435,25 @@ fn rec_key(rec: &Rec) -> (TypeId, &str) {
 /// For Internal Use.
 #[doc(hidden)]
 pub struct ProviderGuard<'a> {
+    container: &'a ProviderContainer,
+    guard: OwnedMutexGuard<()>,
     instant: Instant,
-    _guard: MutexGuard<'a, ()>,
     name: &'a str,
 }
 
 impl Drop for ProviderGuard<'_> {
     fn drop(&mut self) {
+        let mut gates = self.container.gates.lock();
+
+        // the map and this guard hold the only references, no load waits on the gate.
+        if gates.get(self.name).is_some_and(|gate| {
+            Arc::ptr_eq(gate, OwnedMutexGuard::mutex(&self.guard)) && Arc::strong_count(gate) == 2
+        }) {
+            gates.remove(self.name);
+        }
+
+        drop(gates);
+
         let elapsed = self.instant.elapsed().as_millis();
         if elapsed > 250 {
             warn!(
@@ -229,3 +464,26 @@ impl Drop for ProviderGuard<'_> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn gate_removed_when_released() {
+        let container = ProviderContainer::new();
+
+        let guard = container.gate("a").await;
+        let waiting = container.gate("a");
+        let mut waiting = Box::pin(waiting);
+
+        assert!(futures::poll!(&mut waiting).is_pending());
+
+        // a load waits on the gate.
+        drop(guard);
+        assert_eq!(container.gates.lock().len(), 1);
+
+        drop(waiting.await);
+        assert!(container.gates.lock().is_empty());
+    }
+}
@@ -0,0 +1,69 @@
+use crate::{BoxFuture, Result, SerializedLogs};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    sync::{
+        OnceLock,
+        atomic::{AtomicU64, Ordering::Relaxed},
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The state of a commit across multiple providers, written to the [RecoveryLog].
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CommitRecord {
+    /// Identifies the commit. The ids start at the time the process started, in microseconds,
+    /// so they stay unique across restarts.
+    pub id: u64,
+
+    /// The providers already committed.
+    pub committed: Vec<Box<str>>,
+
+    /// The table logs of the transaction by provider, to write again the changes of the
+    /// providers left pending.
+    pub logs: BTreeMap<Box<str>, SerializedLogs>,
+
+    /// The providers prepared but not committed.
+    pub pending: Vec<Box<str>>,
+}
+
+impl CommitRecord {
+    pub(super) fn new(pending: Vec<Box<str>>, logs: BTreeMap<Box<str>, SerializedLogs>) -> Self {
+        static NEXT_ID: OnceLock<AtomicU64> = OnceLock::new();
+
+        let next_id = NEXT_ID.get_or_init(|| {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default();
+
+            AtomicU64::new(now.as_micros() as u64)
+        });
+
+        Self {
+            id: next_id.fetch_add(1, Relaxed),
+            committed: Vec::new(),
+            logs,
+            pending,
+        }
+    }
+}
+
+/// Persists the commits across multiple providers, so a commit interrupted after some
+/// providers were committed can be recovered.
+///
+/// A record left with pending providers, because the process stopped or a provider failed
+/// in the commit phase, lists the providers whose logs must be written again by
+/// [ProviderContainer::recover](super::ProviderContainer::recover).
+pub trait RecoveryLog: Send + Sync + 'static {
+    /// Called once all the providers are prepared, before the first one is committed.
+    ///
+    /// All the providers are pending. An error cancels all the providers.
+    fn prepared<'a>(&'a self, record: &'a CommitRecord) -> BoxFuture<'a, Result<()>>;
+
+    /// Called at the end of the commit phase. The commit is complete when no provider is
+    /// pending, otherwise the pending providers were cancelled.
+    fn finished<'a>(&'a self, record: &'a CommitRecord) -> BoxFuture<'a, Result<()>>;
+
+    /// The records whose commit is not complete, in the order they were prepared.
+    fn pending(&self) -> BoxFuture<'_, Result<Vec<CommitRecord>>>;
+}
@@ -1,31 +1,143 @@
-use super::{LoadAll, LoadArgs, ProviderContainer};
-use crate::{BoxFuture, Entity, Result};
-use std::ops::Deref;
+use super::{CommitPhase, CommitRecord, LoadAll, LoadArgs, ProviderContainer, RecoveryLog};
+use crate::{BoxFuture, Entity, Error, Logs, Result, SerializedLogs};
+use std::{collections::BTreeMap, ops::Deref};
+use tracing::error;
 
 pub struct TransactionProvider<'a>(pub(super) &'a ProviderContainer);
 
 impl<'a> TransactionProvider<'a> {
+    /// Commits all the providers in two phases.
+    ///
+    /// All providers are prepared first and are all cancelled if one of them fails. Then
+    /// the providers are committed, a failure cancels the remaining providers. Since
+    /// the providers already committed cannot be rolled back, the commit is written to the
+    /// [RecoveryLog](super::RecoveryLog) of the container, when there is more than one provider, to allow
+    /// a recovery.
+    ///
+    /// The record has no logs to replay, use [CtxTransaction::commit](crate::CtxTransaction::commit)
+    /// to record them.
     pub fn commit(&self) -> BoxFuture<'_, Result<()>> {
+        self.commit_logs(None)
+    }
+
+    /// Commits all the providers, recording the table `logs` of the transaction for
+    /// the [RecoveryLog](super::RecoveryLog).
+    pub(crate) fn commit_logs<'b>(&'b self, logs: Option<&'b Logs>) -> BoxFuture<'b, Result<()>> {
         Box::pin(async move {
-            let mut error = None;
+            let log = match self
+                .0
+                .recovery_log()
+                .filter(|_| self.0.providers().nth(1).is_some())
+            {
+                Some(log) => {
+                    let pending = self.0.providers().map(|(name, _)| name.into()).collect();
+
+                    let logs = match logs.map(SerializedLogs::by_provider).transpose() {
+                        Ok(logs) => logs.unwrap_or_else(BTreeMap::new),
+                        Err(e) => {
+                            self.cancel_all();
+                            return Err(e);
+                        }
+                    };
 
-            for provider in self.0.providers() {
-                if error.is_none() {
-                    if let Err(e) = provider.commit().await {
-                        error = Some(e);
+                    Some((log, CommitRecord::new(pending, logs)))
+                }
+                None => None,
+            };
+
+            self.commit_record(log).await
+        })
+    }
+
+    /// Commits all the providers, writing `log` to its recovery log when there is one.
+    pub(super) fn commit_record<'b>(
+        &'b self,
+        log: Option<(&'b dyn RecoveryLog, CommitRecord)>,
+    ) -> BoxFuture<'b, Result<()>> {
+        Box::pin(async move {
+            for (name, provider) in self.0.providers() {
+                if let Err(e) = provider.prepare().await {
+                    self.cancel_all();
+                    return Err(commit_error(e, CommitPhase::Prepare, name, Vec::new()));
+                }
+            }
+
+            if let Some((log, record)) = &log
+                && let Err(e) = log.prepared(record).await
+            {
+                self.cancel_all();
+                return Err(e);
+            }
+
+            let mut committed = Vec::new();
+            let mut result = Ok(());
+
+            for (name, provider) in self.0.providers() {
+                if let Err(e) = provider.commit().await {
+                    self.cancel_all();
+
+                    if !committed.is_empty() {
+                        error!(
+                            provider = name,
+                            committed = ?committed,
+                            error = %e,
+                            "transaction partially committed, recover it from the recovery log"
+                        );
                     }
-                } else {
-                    provider.cancel();
+
+                    result = Err(commit_error(
+                        e,
+                        CommitPhase::Commit,
+                        name,
+                        committed.clone(),
+                    ));
+                    break;
+                }
+
+                committed.push(Box::<str>::from(name));
+            }
+
+            if let Some((log, mut record)) = log {
+                record.pending.retain(|name| !committed.contains(name));
+                record.committed = committed;
+
+                if let Err(e) = log.finished(&record).await {
+                    error!(id = record.id, error = %e, "commit not written to the recovery log");
                 }
             }
 
-            match error {
-                Some(err) => Err(err),
-                None => Ok(()),
+            result
+        })
+    }
+
+    /// Rolls back all the providers to the savepoint `name`.
+    pub fn rollback_to<'b>(&'b self, name: &'b str) -> BoxFuture<'b, Result<()>> {
+        Box::pin(async move {
+            for (_, provider) in self.0.providers() {
+                provider.rollback_to(name).await?;
             }
+
+            Ok(())
         })
     }
 
+    /// Creates the savepoint `name` on all the providers.
+    pub fn savepoint<'b>(&'b self, name: &'b str) -> BoxFuture<'b, Result<()>> {
+        Box::pin(async move {
+            for (_, provider) in self.0.providers() {
+                provider.savepoint(name).await?;
+            }
+
+            Ok(())
+        })
+    }
+
+    pub(crate) fn cancel_all(&self) {
+        for (_, provider) in self.0.providers() {
+            provider.cancel();
+        }
+    }
+
     #[inline]
     pub fn container(&self) -> &'a ProviderContainer {
         self.0
@@ -43,9 +155,21 @@ impl Deref for TransactionProvider<'_> {
 
 impl Drop for TransactionProvider<'_> {
     fn drop(&mut self) {
-        for provider in self.0.providers() {
-            provider.cancel();
-        }
+        self.cancel_all();
+    }
+}
+
+fn commit_error(
+    error: Error,
+    phase: CommitPhase,
+    provider: &str,
+    committed: Vec<Box<str>>,
+) -> Error {
+    Error::TransactionCommit {
+        committed,
+        error: Box::new(error),
+        phase,
+        provider: provider.into(),
     }
 }
 
@@ -2,6 +2,25 @@ use crate::{BoxFuture, Entity, Result};
 
 pub trait Upsert<E: Entity>: Send + Sync {
     fn upsert<'a>(&'a self, k: &'a E::Key, v: &'a E) -> BoxFuture<'a, Result<()>>;
+
+    /// Upserts many rows at once. The default implementation calls [upsert](Upsert::upsert)
+    /// for each row, a provider can override it to send the rows in fewer round trips.
+    fn upsert_all<'a>(&'a self, rows: &'a [(E::Key, E)]) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            for (k, v) in rows {
+                self.upsert(k, v).await?;
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Upserts many rows whose keys were not found in the ctx. The default implementation calls
+    /// [upsert_all](Upsert::upsert_all), a provider can override it to use a faster insert path
+    /// but it must still update the rows that already exist in the storage.
+    fn insert_all<'a>(&'a self, rows: &'a [(E::Key, E)]) -> BoxFuture<'a, Result<()>> {
+        self.upsert_all(rows)
+    }
 }
 
 impl<E, PROVIDER> Upsert<E> for &PROVIDER
@@ -13,6 +32,14 @@ where
     fn upsert<'a>(&'a self, k: &'a E::Key, v: &'a E) -> BoxFuture<'a, Result<()>> {
         (**self).upsert(k, v)
     }
+
+    fn upsert_all<'a>(&'a self, rows: &'a [(E::Key, E)]) -> BoxFuture<'a, Result<()>> {
+        (**self).upsert_all(rows)
+    }
+
+    fn insert_all<'a>(&'a self, rows: &'a [(E::Key, E)]) -> BoxFuture<'a, Result<()>> {
+        (**self).insert_all(rows)
+    }
 }
 
 /// This trait is implemented when the entity or the key must be changed while insert or update is performed.
@@ -0,0 +1,85 @@
+use crate::{BoxFuture, Ctx, Result};
+use async_cell_lock::QueueRwLockQueueGuard;
+use std::time::Duration;
+use tracing::warn;
+
+/// The options of [retry_transaction].
+#[derive(Clone, Debug)]
+pub struct RetryOptions {
+    /// The maximum number of times the transaction is run, including the first one.
+    pub max_attempts: u32,
+
+    /// The delay before the first retry, doubled on each following retry.
+    pub backoff: Duration,
+
+    /// The maximum delay between two attempts.
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryOptions {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            backoff: Duration::from_millis(50),
+            max_backoff: Duration::from_secs(2),
+        }
+    }
+}
+
+/// Runs a transaction and runs it again when it fails with a transient error, see
+/// [Error::is_transient](crate::Error::is_transient).
+///
+/// `f` must create the transaction from the guard and commit it. On a failure, the transaction
+/// is dropped with its logs and the providers are cancelled before the next attempt.
+///
+/// ```ignore
+/// let logs = retry_transaction(&ctx, &RetryOptions::default(), |ctx| {
+///     let entity = entity.clone();
+///
+///     Box::pin(async move {
+///         let mut trx = ctx.transaction(user_id);
+///         trx.insert(id, entity).await?;
+///         trx.commit().await
+///     })
+/// })
+/// .await?;
+/// ```
+pub async fn retry_transaction<'a, T, F>(
+    ctx: &QueueRwLockQueueGuard<'a, Ctx>,
+    options: &RetryOptions,
+    mut f: F,
+) -> Result<T>
+where
+    F: for<'b> FnMut(&'b QueueRwLockQueueGuard<'a, Ctx>) -> BoxFuture<'b, Result<T>>,
+{
+    let mut attempt = 1;
+    let mut delay = options.backoff;
+
+    loop {
+        let e = match f(ctx).await {
+            Ok(v) => return Ok(v),
+            Err(e) => e,
+        };
+
+        // a transaction left open by `f` must not be continued by the next attempt, dropping a
+        // transaction cancels all the providers.
+        drop(ctx.provider.transaction());
+
+        if attempt >= options.max_attempts || !e.is_transient() {
+            return Err(e);
+        }
+
+        warn!(
+            attempt,
+            max_attempts = options.max_attempts,
+            delay_ms = delay.as_millis(),
+            error = %e,
+            "transaction failed with a transient error, retrying"
+        );
+
+        tokio::time::sleep(delay).await;
+
+        attempt += 1;
+        delay = delay.saturating_mul(2).min(options.max_backoff);
+    }
+}
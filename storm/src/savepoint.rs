@@ -0,0 +1,87 @@
+use crate::{EntityAccessor, Error, Logs, Result, registry::InitCell};
+use std::marker::PhantomData;
+
+/// A point of a [CtxTransaction](crate::CtxTransaction) that can be restored with
+/// [CtxTransaction::rollback_to](crate::CtxTransaction::rollback_to).
+pub struct Savepoint {
+    pub(crate) id: usize,
+    pub(crate) logs: Logs,
+}
+
+pub(crate) fn savepoint_name(id: usize) -> String {
+    format!("storm_sp{id}")
+}
+
+type SnapshotFn = fn(logs: &Logs, out: &mut Logs) -> Result<()>;
+
+/// Copies the table logs into `out`.
+///
+/// The index logs are not copied, they are rebuilt from the table logs on demand.
+pub(crate) fn snapshot_logs(logs: &Logs) -> Result<Logs> {
+    let mut out = Logs::default();
+
+    for f in SNAPSHOTS.get() {
+        f(logs, &mut out)?;
+    }
+
+    Ok(out)
+}
+
+/// Private: use by a macro.
+#[doc(hidden)]
+pub fn __register_snapshot(f: SnapshotFn) {
+    SNAPSHOTS.get_mut().push(f);
+}
+
+static SNAPSHOTS: InitCell<Vec<SnapshotFn>> = InitCell::new(Vec::new());
+
+/// Private: use by a macro to select [__SnapshotClone] when the entity is `Clone`
+/// and [__SnapshotNoClone] otherwise.
+#[doc(hidden)]
+pub struct __SnapshotProbe<E>(PhantomData<E>);
+
+impl<E> __SnapshotProbe<E> {
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+#[doc(hidden)]
+pub trait __SnapshotClone {
+    fn snapshot_fn(&self) -> SnapshotFn;
+}
+
+impl<E: EntityAccessor + Clone> __SnapshotClone for __SnapshotProbe<E> {
+    fn snapshot_fn(&self) -> SnapshotFn {
+        snapshot_table::<E>
+    }
+}
+
+#[doc(hidden)]
+pub trait __SnapshotNoClone {
+    fn snapshot_fn(&self) -> SnapshotFn;
+}
+
+impl<E: EntityAccessor> __SnapshotNoClone for &__SnapshotProbe<E> {
+    fn snapshot_fn(&self) -> SnapshotFn {
+        snapshot_unsupported::<E>
+    }
+}
+
+fn snapshot_table<E: EntityAccessor + Clone>(logs: &Logs, out: &mut Logs) -> Result<()> {
+    let var = E::tbl_var();
+
+    if let Some(log) = logs.get(var) {
+        out.insert(var, log.clone());
+    }
+
+    Ok(())
+}
+
+fn snapshot_unsupported<E: EntityAccessor>(logs: &Logs, _out: &mut Logs) -> Result<()> {
+    match logs.contains(E::tbl_var()) {
+        true => Err(Error::SavepointNotSupported),
+        false => Ok(()),
+    }
+}
@@ -0,0 +1,263 @@
+use crate::{
+    BoxFuture, CtxTypeInfo, EntityAccessor, Error, Logs, Result,
+    provider::{Delete, TransactionProvider, Upsert},
+    registry::InitCell,
+};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::Value;
+use std::{collections::BTreeMap, marker::PhantomData};
+
+/// The table logs of a committed transaction in a serializable form, keyed by
+/// [CtxTypeInfo::NAME], so they can be sent to another process and applied with
+/// [Ctx::apply_serialized_log](crate::Ctx::apply_serialized_log).
+///
+/// The index logs are not serialized, they are rebuilt from the table logs when applied.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SerializedLogs(BTreeMap<Box<str>, Value>);
+
+impl SerializedLogs {
+    /// Encodes the table logs. The entities in the logs must implement `Serialize` and
+    /// `Deserialize`.
+    pub fn from_logs(logs: &Logs) -> Result<Self> {
+        let mut map = BTreeMap::new();
+
+        for codec in CODECS.get() {
+            if let Some(value) = (codec.encode)(logs)? {
+                map.insert(codec.name.into(), value);
+            }
+        }
+
+        Ok(Self(map))
+    }
+
+    /// Encodes the table logs, split by the name of the provider of their entity.
+    pub fn by_provider(logs: &Logs) -> Result<BTreeMap<Box<str>, Self>> {
+        let mut map = BTreeMap::<Box<str>, Self>::new();
+
+        for codec in CODECS.get() {
+            if let Some(value) = (codec.encode)(logs)? {
+                map.entry(codec.provider.into())
+                    .or_default()
+                    .0
+                    .insert(codec.name.into(), value);
+            }
+        }
+
+        Ok(map)
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Decodes the table logs.
+    pub fn into_logs(self) -> Result<Logs> {
+        let codecs = CODECS.get();
+        let mut logs = Logs::default();
+
+        for (name, value) in self.0 {
+            let codec = codecs
+                .iter()
+                .find(|c| c.name == &*name)
+                .ok_or_else(|| Error::ConvertFailed(format!("unknown entity `{name}`")))?;
+
+            (codec.decode)(value, &mut logs)?;
+        }
+
+        Ok(logs)
+    }
+
+    /// Writes the rows of the logs again, an upsert for each row present and a delete for
+    /// each row removed, so replaying logs already written changes nothing.
+    ///
+    /// The tables are written in the order of their names and the entities must implement
+    /// [Upsert] and [Delete] on the [TransactionProvider].
+    pub async fn replay(&self, provider: &TransactionProvider<'_>) -> Result<()> {
+        let codecs = CODECS.get();
+
+        for (name, value) in &self.0 {
+            let codec = codecs
+                .iter()
+                .find(|c| c.name == &**name)
+                .ok_or_else(|| Error::ConvertFailed(format!("unknown entity `{name}`")))?;
+
+            (codec.replay)(value.clone(), provider).await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Private: use by a macro.
+#[doc(hidden)]
+pub struct LogCodec {
+    decode: fn(Value, &mut Logs) -> Result<()>,
+    encode: fn(&Logs) -> Result<Option<Value>>,
+    name: &'static str,
+}
+
+/// Private: use by a macro.
+#[doc(hidden)]
+pub type ReplayFn = for<'a, 'b> fn(Value, &'a TransactionProvider<'b>) -> BoxFuture<'a, Result<()>>;
+
+/// Private: use by a macro.
+#[doc(hidden)]
+pub fn __register_log_codec(codec: LogCodec, provider: &'static str, replay: ReplayFn) {
+    CODECS.get_mut().push(Codec {
+        decode: codec.decode,
+        encode: codec.encode,
+        name: codec.name,
+        provider,
+        replay,
+    });
+}
+
+/// A [LogCodec] with the provider of its entity.
+struct Codec {
+    decode: fn(Value, &mut Logs) -> Result<()>,
+    encode: fn(&Logs) -> Result<Option<Value>>,
+    name: &'static str,
+    provider: &'static str,
+    replay: ReplayFn,
+}
+
+static CODECS: InitCell<Vec<Codec>> = InitCell::new(Vec::new());
+
+/// Private: use by a macro to select [__LogCodecSerde] when the entity implements serde
+/// and [__LogCodecNoSerde] otherwise.
+#[doc(hidden)]
+pub struct __LogCodecProbe<E>(PhantomData<E>);
+
+impl<E> __LogCodecProbe<E> {
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+#[doc(hidden)]
+pub trait __LogCodecSerde {
+    fn log_codec(&self) -> LogCodec;
+}
+
+impl<E> __LogCodecSerde for __LogCodecProbe<E>
+where
+    E: CtxTypeInfo + DeserializeOwned + EntityAccessor + Serialize,
+    E::Key: DeserializeOwned + Serialize,
+{
+    fn log_codec(&self) -> LogCodec {
+        LogCodec {
+            decode: decode::<E>,
+            encode: encode::<E>,
+            name: E::NAME,
+        }
+    }
+}
+
+#[doc(hidden)]
+pub trait __LogCodecNoSerde {
+    fn log_codec(&self) -> LogCodec;
+}
+
+impl<E: CtxTypeInfo + EntityAccessor> __LogCodecNoSerde for &__LogCodecProbe<E> {
+    fn log_codec(&self) -> LogCodec {
+        LogCodec {
+            decode: decode_unsupported::<E>,
+            encode: encode_unsupported::<E>,
+            name: E::NAME,
+        }
+    }
+}
+
+#[doc(hidden)]
+pub trait __LogReplay {
+    fn log_replay(&self) -> ReplayFn;
+}
+
+impl<E> __LogReplay for __LogCodecProbe<E>
+where
+    E: CtxTypeInfo + DeserializeOwned + EntityAccessor,
+    E::Key: DeserializeOwned,
+    for<'b> TransactionProvider<'b>: Delete<E> + Upsert<E>,
+{
+    fn log_replay(&self) -> ReplayFn {
+        replay::<E>
+    }
+}
+
+#[doc(hidden)]
+pub trait __LogNoReplay {
+    fn log_replay(&self) -> ReplayFn;
+}
+
+impl<E: CtxTypeInfo> __LogNoReplay for &__LogCodecProbe<E> {
+    fn log_replay(&self) -> ReplayFn {
+        replay_unsupported::<E>
+    }
+}
+
+fn decode<E>(value: Value, logs: &mut Logs) -> Result<()>
+where
+    E: DeserializeOwned + EntityAccessor,
+    E::Key: DeserializeOwned,
+{
+    let vec: Vec<(E::Key, Option<E>)> = serde_json::from_value(value).map_err(Error::std)?;
+    logs.insert(E::tbl_var(), vec.into_iter().collect());
+    Ok(())
+}
+
+fn encode<E>(logs: &Logs) -> Result<Option<Value>>
+where
+    E: EntityAccessor + Serialize,
+    E::Key: Serialize,
+{
+    logs.get(E::tbl_var())
+        .map(|log| serde_json::to_value(log.iter().collect::<Vec<_>>()).map_err(Error::std))
+        .transpose()
+}
+
+fn decode_unsupported<E: CtxTypeInfo>(_value: Value, _logs: &mut Logs) -> Result<()> {
+    Err(not_serializable::<E>())
+}
+
+fn encode_unsupported<E: CtxTypeInfo + EntityAccessor>(logs: &Logs) -> Result<Option<Value>> {
+    match logs.contains(E::tbl_var()) {
+        true => Err(not_serializable::<E>()),
+        false => Ok(None),
+    }
+}
+
+fn replay<'a, E>(value: Value, provider: &'a TransactionProvider<'_>) -> BoxFuture<'a, Result<()>>
+where
+    E: DeserializeOwned + EntityAccessor,
+    E::Key: DeserializeOwned,
+    for<'b> TransactionProvider<'b>: Delete<E> + Upsert<E>,
+{
+    Box::pin(async move {
+        let vec: Vec<(E::Key, Option<E>)> = serde_json::from_value(value).map_err(Error::std)?;
+
+        for (k, v) in &vec {
+            match v {
+                Some(v) => provider.upsert(k, v).await?,
+                None => provider.delete(k).await?,
+            }
+        }
+
+        Ok(())
+    })
+}
+
+fn replay_unsupported<'a, E: CtxTypeInfo>(
+    _value: Value,
+    _provider: &'a TransactionProvider<'_>,
+) -> BoxFuture<'a, Result<()>> {
+    Box::pin(std::future::ready(Err(Error::ConvertFailed(format!(
+        "`{}` logs cannot be replayed",
+        E::NAME
+    )))))
+}
+
+fn not_serializable<E: CtxTypeInfo>() -> Error {
+    Error::ConvertFailed(format!("`{}` logs are not serializable", E::NAME))
+}
@@ -0,0 +1,127 @@
+use crate::Entity;
+use parking_lot::Mutex;
+use rustc_hash::FxHashMap;
+use std::any::{Any, TypeId};
+use tokio::sync::broadcast::{self, Receiver, Sender};
+
+/// The number of changes kept for a lagging subscriber of an entity type, the oldest
+/// changes are dropped after that and the receiver gets a `Lagged` error.
+pub const SUBSCRIPTION_CAPACITY: usize = 1024;
+
+/// A committed change of a row, received from [Ctx::subscribe](crate::Ctx::subscribe)
+/// once the log is applied.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Change<K> {
+    pub key: K,
+
+    /// The row existed before the change. Always false for a row of a
+    /// [LazyTable](crate::LazyTable) that was not loaded.
+    pub old: bool,
+
+    /// The row exists after the change.
+    pub new: bool,
+}
+
+/// The changes of an entity type collected while a log is applied, sent once the whole log
+/// is applied, when the indexes are up to date.
+pub(crate) struct Changes<K> {
+    changes: Vec<Change<K>>,
+    sender: Option<Sender<Change<K>>>,
+}
+
+impl<K: Clone> Changes<K> {
+    /// Collects a change when the entity type has a subscriber.
+    pub fn push(&mut self, key: &K, old: bool, new: bool) {
+        if self.sender.is_some() {
+            self.changes.push(Change {
+                key: key.clone(),
+                old,
+                new,
+            });
+        }
+    }
+}
+
+type Deferred = Box<dyn FnOnce() + Send + Sync>;
+
+/// The broadcast senders, by entity type, and the changes waiting for the log to be applied.
+#[derive(Default)]
+pub(crate) struct Subscriptions {
+    deferred: Vec<Deferred>,
+
+    /// Each value is a `Sender<Change<E::Key>>`.
+    senders: Mutex<FxHashMap<TypeId, Box<dyn Any + Send + Sync>>>,
+}
+
+impl Subscriptions {
+    /// Starts collecting the changes of the entity type applied from a log.
+    pub fn changes<E: Entity>(&self) -> Changes<E::Key> {
+        Changes {
+            changes: Vec::new(),
+            sender: self.sender::<E>(),
+        }
+    }
+
+    /// Keeps the collected changes until [send_deferred](Self::send_deferred).
+    pub fn defer<K: Send + Sync + 'static>(&mut self, changes: Changes<K>) {
+        let Changes {
+            changes,
+            sender: Some(sender),
+        } = changes
+        else {
+            return;
+        };
+
+        if changes.is_empty() {
+            return;
+        }
+
+        self.deferred.push(Box::new(move || {
+            for change in changes {
+                let _ = sender.send(change);
+            }
+        }));
+    }
+
+    /// Sends the changes collected while the log was applied.
+    pub fn send_deferred(&mut self) {
+        for send in self.deferred.drain(..) {
+            send();
+        }
+    }
+
+    /// Returns the sender of the entity type if it has at least one receiver.
+    fn sender<E: Entity>(&self) -> Option<Sender<Change<E::Key>>> {
+        let mut map = self.senders.lock();
+        let type_id = TypeId::of::<E>();
+
+        let sender = map
+            .get(&type_id)?
+            .downcast_ref::<Sender<Change<E::Key>>>()
+            .filter(|s| s.receiver_count() > 0)
+            .cloned();
+
+        // all the receivers were dropped.
+        if sender.is_none() {
+            map.remove(&type_id);
+        }
+
+        sender
+    }
+
+    pub fn subscribe<E: Entity>(&self) -> Receiver<Change<E::Key>> {
+        let mut map = self.senders.lock();
+        let type_id = TypeId::of::<E>();
+
+        if let Some(sender) = map
+            .get(&type_id)
+            .and_then(|s| s.downcast_ref::<Sender<Change<E::Key>>>())
+        {
+            return sender.subscribe();
+        }
+
+        let (sender, receiver) = broadcast::channel(SUBSCRIPTION_CAPACITY);
+        map.insert(type_id, Box::new(sender));
+        receiver
+    }
+}
@@ -1,7 +1,7 @@
 use crate::{
     AsRefAsync, BoxFuture, ClearEvent, Clearable, Ctx, CtxTypeInfo, Entity, EntityAccessor,
     EntityOf, Gc, Get, GetMut, Logs, NotifyTag, ProviderContainer, RefIntoIterator, Result, Tag,
-    Touchable, TouchedEvent, provider::LoadAll,
+    Touchable, TouchedEvent, logs::TableLog, provider::LoadAll, subscriptions::Changes,
 };
 use rayon::iter::IntoParallelIterator;
 use std::ops::Deref;
@@ -36,35 +36,50 @@ impl<E: Entity> VecTable<E> {
             return false;
         }
 
+        let mut changes = ctx.subscriptions.changes::<E>();
+
         let Some(tbl) = ctx.ctx_ext_obj.get_mut(E::tbl_var()).get_mut() else {
             return false;
         };
 
+        tbl.apply(log, &mut changes);
+        ctx.subscriptions.defer(changes);
+        E::touched().call(ctx);
+
+        true
+    }
+
+    /// Applies the changes of a log to the table, collecting them for the subscribers.
+    fn apply(&mut self, log: TableLog<E>, changes: &mut Changes<E::Key>)
+    where
+        E: CtxTypeInfo + EntityAccessor,
+        E::Key: Into<u32>,
+    {
         for (k, state) in log {
             match state {
-                Some(new) => match tbl.map.entry(k) {
+                Some(new) => match self.map.entry(k) {
                     Entry::Occupied(mut o) => {
                         E::applied().call(o.key(), Some(o.get()), Some(&new));
+                        changes.push(o.key(), true, true);
                         o.insert(new);
                     }
                     Entry::Vacant(v) => {
                         E::applied().call(v.key(), None, Some(&new));
+                        changes.push(v.key(), false, true);
                         v.insert(new);
                     }
                 },
                 None => {
-                    if let Some(old) = tbl.map.remove(&k) {
+                    if let Some(old) = self.map.remove(&k) {
                         E::applied().call(&k, Some(&old), None);
+                        changes.push(&k, true, false);
                     }
                 }
             }
         }
 
-        tbl.update_metrics();
-        tbl.tag.notify();
-        E::touched().call(ctx);
-
-        true
+        self.update_metrics();
+        self.tag.notify();
     }
 
     #[inline]
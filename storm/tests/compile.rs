@@ -53,6 +53,26 @@ async fn read() -> Result<()> {
     .await
 }
 
+#[tokio::test]
+async fn preload() -> Result<()> {
+    async_cell_lock::with_deadlock_check(
+        async move {
+            let ctx = create_ctx();
+            let ctx = ctx.read().await?;
+
+            let timings = ctx.preload::<(Entity1, Entity2, Entity3)>().await?;
+            let names = timings.iter().map(|t| t.name).collect::<Vec<_>>();
+
+            assert_eq!(names, ["Entity1", "Entity2", "Entity3"]);
+            assert!(ctx.tbl_of_opt::<Entity3>().is_some());
+
+            Ok(())
+        },
+        "preload",
+    )
+    .await
+}
+
 #[tokio::test]
 async fn transaction() -> Result<()> {
     async_cell_lock::with_deadlock_check(
@@ -0,0 +1,59 @@
+#![allow(clippy::unwrap_used)]
+
+use storm::{MemDelete, MemLoad, MemSave, Result, prelude::*, provider::MemFactory};
+use uuid::Uuid;
+
+fn create_ctx() -> QueueRwLock<Ctx> {
+    let mut provider = ProviderContainer::new();
+    provider.register("", MemFactory::new());
+    QueueRwLock::new(provider.into(), "ctx")
+}
+
+#[tokio::test]
+async fn insert_all_skips_unchanged_and_keeps_last() -> Result<()> {
+    async_cell_lock::with_deadlock_check(
+        async move {
+            let ctx = create_ctx();
+            let ctx = ctx.queue().await?;
+            let mut trx = ctx.transaction(Uuid::nil());
+            let mut tbl = trx.tbl_of::<Entity1>().await?;
+
+            tbl.insert(1, Entity1 { name: "a".into() }).await?;
+
+            let count = tbl
+                .insert_all(vec![
+                    (1, Entity1 { name: "a".into() }),
+                    (2, Entity1 { name: "b".into() }),
+                    (2, Entity1 { name: "c".into() }),
+                ])
+                .await?;
+
+            // the rows of a key are upserted in turn.
+            assert_eq!(count, 2);
+            assert_eq!(tbl.get(&2).unwrap().name, "c");
+
+            let log = trx.commit().await?;
+            let mut ctx = ctx.write().await?;
+
+            ctx.apply_log(log);
+
+            let tbl = ctx.tbl_of::<Entity1>().await?;
+
+            assert_eq!(tbl.len(), 2);
+            assert_eq!(tbl.get(&2).unwrap().name, "c");
+
+            Ok(())
+        },
+        "insert_all_skips_unchanged_and_keeps_last",
+    )
+    .await
+}
+
+#[derive(Clone, Ctx, Debug, MemDelete, MemLoad, MemSave, PartialEq)]
+struct Entity1 {
+    name: String,
+}
+
+impl Entity for Entity1 {
+    type Key = u32;
+}
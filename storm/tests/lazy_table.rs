@@ -0,0 +1,106 @@
+#![allow(clippy::unwrap_used)]
+
+use storm::{
+    EntityAccessor, MemDelete, MemLoad, MemSave, Result, prelude::*, provider::MemFactory,
+};
+use uuid::Uuid;
+
+fn create_ctx(factory: &MemFactory) -> QueueRwLock<Ctx> {
+    let mut provider = ProviderContainer::new();
+    provider.register("", factory.clone());
+    QueueRwLock::new(provider.into(), "ctx")
+}
+
+async fn seed(factory: &MemFactory) -> Result<()> {
+    let ctx = create_ctx(factory);
+    let ctx = ctx.queue().await?;
+    let mut trx = ctx.transaction(Uuid::nil());
+
+    trx.insert_all((1..=4).map(|id| (id, Lazy { id }))).await?;
+    trx.commit().await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn load_rows_on_demand() -> Result<()> {
+    async_cell_lock::with_deadlock_check(
+        async move {
+            let factory = MemFactory::new();
+            seed(&factory).await?;
+
+            let ctx = create_ctx(&factory);
+
+            {
+                let ctx = ctx.read().await?;
+                let tbl = ctx.tbl_of::<Lazy>().await?;
+
+                // nothing is loaded until a row is requested.
+                assert_eq!(tbl.loaded_len(), 0);
+                assert!(tbl.get(&1).is_none());
+
+                let v = Lazy::entity_of(&ctx, &1).await?;
+                assert_eq!(v, Some(&Lazy { id: 1 }));
+                assert_eq!(tbl.get(&1), Some(&Lazy { id: 1 }));
+
+                // the absent keys are kept.
+                assert!(tbl.get_or_load(ctx.provider(), &9).await?.is_none());
+                assert!(tbl.is_loaded(&9));
+
+                tbl.preload(ctx.provider(), &[2, 3]).await?;
+                assert_eq!(tbl.loaded_len(), 4);
+            }
+
+            // a row that is not loaded can be removed.
+            {
+                let ctx = ctx.queue().await?;
+                let mut trx = ctx.transaction(Uuid::nil());
+
+                assert!(trx.remove::<Lazy>(4).await?);
+
+                let log = trx.commit().await?;
+                ctx.write().await?.apply_log(log);
+            }
+
+            let mut ctx = ctx.write().await?;
+            let tbl = ctx.tbl_of::<Lazy>().await?;
+
+            // the least recently used rows are evicted above the capacity when the log is applied.
+            assert!(tbl.is_loaded(&4));
+            assert!(tbl.get(&4).is_none());
+            assert_eq!(tbl.loaded_len(), 3);
+            assert!(!tbl.is_loaded(&1));
+            assert!(!tbl.is_loaded(&9));
+
+            // the rows loaded through a shared borrow are evicted by the gc.
+            assert_eq!(
+                tbl.get_or_load(ctx.provider(), &1).await?,
+                Some(&Lazy { id: 1 })
+            );
+            assert_eq!(tbl.loaded_len(), 4);
+
+            ctx.gc();
+
+            let tbl = ctx.tbl_of::<Lazy>().await?;
+
+            assert_eq!(tbl.loaded_len(), 3);
+            assert!(!tbl.is_loaded(&2));
+            assert!(tbl.is_loaded(&1));
+            assert!(tbl.is_loaded(&4));
+
+            Ok(())
+        },
+        "load_rows_on_demand",
+    )
+    .await
+}
+
+#[derive(Clone, Ctx, Debug, MemDelete, MemLoad, MemSave, PartialEq)]
+#[storm(collection = "lazy_table", capacity = 3)]
+struct Lazy {
+    id: u32,
+}
+
+impl Entity for Lazy {
+    type Key = u32;
+}
@@ -0,0 +1,68 @@
+#![allow(clippy::unwrap_used)]
+
+use futures::join;
+use std::sync::Mutex;
+use storm::{
+    BoxFuture, Entity, Result,
+    provider::{LoadArgs, LoadMany, LoadManyRows, LoadOne, ProviderContainer},
+};
+
+/// The keys of each batch loaded by the provider.
+static BATCHES: Mutex<Vec<Vec<u32>>> = Mutex::new(Vec::new());
+
+#[tokio::test]
+async fn load_one_coalesced() -> Result<()> {
+    let provider = ProviderContainer::new();
+
+    let (a, b, c, d) = join!(
+        LoadOne::<Counted>::load_one(&provider, &1),
+        LoadOne::<Counted>::load_one(&provider, &2),
+        LoadOne::<Counted>::load_one(&provider, &20),
+        LoadOne::<Counted>::load_one(&provider, &1),
+    );
+
+    assert_eq!(a?, Some(Counted(1)));
+    assert_eq!(b?, Some(Counted(2)));
+    assert_eq!(c?, None);
+    assert_eq!(d?, Some(Counted(1)));
+
+    // the duplicated key is loaded again on its own.
+    assert_eq!(*BATCHES.lock().unwrap(), [vec![1, 2, 20, 1], vec![1]]);
+
+    Ok(())
+}
+
+#[derive(Debug, PartialEq)]
+struct Counted(u32);
+
+impl Entity for Counted {
+    type Key = u32;
+}
+
+impl LoadOne<Counted> for ProviderContainer {
+    fn load_one_with_args<'a>(
+        &'a self,
+        k: &'a u32,
+        args: LoadArgs,
+    ) -> BoxFuture<'a, Result<Option<Counted>>> {
+        self.load_one_coalesced(k, args)
+    }
+}
+
+impl LoadMany<Counted> for ProviderContainer {
+    fn load_many_with_args<'a>(
+        &'a self,
+        keys: &'a [u32],
+        _args: LoadArgs,
+    ) -> BoxFuture<'a, Result<LoadManyRows<Counted>>> {
+        Box::pin(async move {
+            BATCHES.lock().unwrap().push(keys.to_vec());
+
+            Ok(keys
+                .iter()
+                .filter(|k| **k < 10)
+                .map(|k| (*k, Counted(*k)))
+                .collect())
+        })
+    }
+}
@@ -0,0 +1,129 @@
+#![allow(clippy::unwrap_used)]
+
+use std::sync::atomic::{AtomicU32, Ordering::Relaxed};
+use storm::{
+    MemDelete, MemLoad, MemSave, Result,
+    prelude::*,
+    provider::{LoadOne, MemFactory},
+};
+use uuid::Uuid;
+
+fn create_ctx(factory: &MemFactory) -> QueueRwLock<Ctx> {
+    let mut provider = ProviderContainer::new();
+    provider.register("", factory.clone());
+    QueueRwLock::new(provider.into(), "ctx")
+}
+
+fn create_factory() -> MemFactory {
+    let next = AtomicU32::new(1);
+    MemFactory::new().with_key_generator::<Identity, _>(move || next.fetch_add(1, Relaxed))
+}
+
+#[tokio::test]
+async fn reload_after_commit() -> Result<()> {
+    async_cell_lock::with_deadlock_check(
+        async move {
+            let factory = create_factory();
+            let ctx = create_ctx(&factory);
+            let ctx = ctx.queue().await?;
+            let mut trx = ctx.transaction(Uuid::nil());
+            let mut tbl = trx.tbl_of::<Plain>().await?;
+
+            tbl.insert(1, Plain { name: "a".into() }).await?;
+            tbl.insert(2, Plain { name: "b".into() }).await?;
+            tbl.remove(2).await?;
+
+            let log = trx.commit().await?;
+            let mut ctx = ctx.write().await?;
+
+            ctx.apply_log(log);
+
+            // a new ctx must load the committed rows from the provider.
+            let ctx = create_ctx(&factory);
+            let ctx = ctx.read().await?;
+            let tbl = ctx.tbl_of::<Plain>().await?;
+
+            assert_eq!(tbl.get(&1).unwrap().name, "a");
+            assert!(tbl.get(&2).is_none());
+
+            Ok(())
+        },
+        "reload_after_commit",
+    )
+    .await
+}
+
+#[tokio::test]
+async fn rollback_on_drop() -> Result<()> {
+    async_cell_lock::with_deadlock_check(
+        async move {
+            let factory = create_factory();
+            let ctx = create_ctx(&factory);
+
+            {
+                let ctx = ctx.queue().await?;
+                let mut trx = ctx.transaction(Uuid::nil());
+                let mut tbl = trx.tbl_of::<Plain>().await?;
+
+                tbl.insert(1, Plain { name: "a".into() }).await?;
+            }
+
+            let ctx = ctx.read().await?;
+
+            let v: Option<Plain> = ctx.provider().load_one(&1).await?;
+            assert!(v.is_none());
+
+            Ok(())
+        },
+        "rollback_on_drop",
+    )
+    .await
+}
+
+#[tokio::test]
+async fn identity_and_reload_on_upsert() -> Result<()> {
+    async_cell_lock::with_deadlock_check(
+        async move {
+            let factory = create_factory();
+            let ctx = create_ctx(&factory);
+            let ctx = ctx.queue().await?;
+            let mut trx = ctx.transaction(Uuid::nil());
+            let mut tbl = trx.tbl_of::<Identity>().await?;
+
+            let (k1, _) = tbl.insert_mut(0, Identity { name: "a".into() }).await?;
+            let (k2, _) = tbl.insert_mut(0, Identity { name: "b".into() }).await?;
+            let (k3, _) = tbl.insert_mut(k1, Identity { name: "c".into() }).await?;
+
+            assert_eq!((k1, k2, k3), (1, 2, 1));
+            assert_eq!(tbl.get(&1).unwrap().name, "c");
+
+            trx.commit().await?;
+
+            let v: Option<Identity> = ctx.provider().load_one(&2).await?;
+            assert_eq!(v.unwrap().name, "b");
+
+            Ok(())
+        },
+        "identity_and_reload_on_upsert",
+    )
+    .await
+}
+
+#[derive(Clone, Ctx, Debug, MemDelete, MemLoad, MemSave, PartialEq)]
+struct Plain {
+    name: String,
+}
+
+impl Entity for Plain {
+    type Key = u32;
+}
+
+#[derive(Clone, Ctx, Debug, MemDelete, MemLoad, MemSave, PartialEq)]
+#[storm(identity = "id", reload_on_upsert)]
+struct Identity {
+    name: String,
+}
+
+impl Entity for Identity {
+    type Key = u32;
+}
@@ -0,0 +1,164 @@
+#![allow(clippy::unwrap_used)]
+
+use storm::{
+    MemDelete, MemLoad, MemSave, PartitionedEntity, Result, prelude::*, provider::MemFactory,
+};
+use uuid::Uuid;
+
+fn create_ctx(factory: &MemFactory) -> QueueRwLock<Ctx> {
+    let mut provider = ProviderContainer::new();
+    provider.register("", factory.clone());
+    QueueRwLock::new(provider.into(), "ctx")
+}
+
+async fn seed(factory: &MemFactory) -> Result<()> {
+    let ctx = create_ctx(factory);
+    let ctx = ctx.queue().await?;
+    let mut trx = ctx.transaction(Uuid::nil());
+
+    trx.insert_all((1..=4).map(|id| {
+        (
+            id,
+            Invoice {
+                id,
+                year: 2023 + id % 2,
+            },
+        )
+    }))
+    .await?;
+
+    trx.commit().await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn load_partitions_on_demand() -> Result<()> {
+    async_cell_lock::with_deadlock_check(
+        async move {
+            let factory = MemFactory::new();
+            seed(&factory).await?;
+
+            let ctx = create_ctx(&factory);
+
+            {
+                let ctx = ctx.read().await?;
+                let tbl = ctx.tbl_of::<Invoice>().await?;
+
+                // nothing is loaded until a partition is requested.
+                assert_eq!(tbl.loaded_len(), 0);
+                assert!(tbl.get(&2).is_none());
+
+                let invoices = ctx.tbl_of_partition::<Invoice>(&2023).await?;
+                assert_eq!(invoices.len(), 2);
+                assert!(invoices.contains_key(&2));
+                assert!(invoices.contains_key(&4));
+
+                assert!(tbl.is_loaded(&2023));
+                assert!(!tbl.is_loaded(&2024));
+                assert_eq!(tbl.get(&2), Some(&Invoice { id: 2, year: 2023 }));
+            }
+
+            // the upserts are routed to the partition of the row.
+            {
+                let ctx = ctx.queue().await?;
+                let mut trx = ctx.transaction(Uuid::nil());
+
+                trx.insert(5, Invoice { id: 5, year: 2023 }).await?;
+                trx.insert(2, Invoice { id: 2, year: 2024 }).await?;
+
+                let log = trx.commit().await?;
+                ctx.write().await?.apply_log(log);
+            }
+
+            let mut ctx = ctx.write().await?;
+
+            {
+                let invoices = ctx.tbl_of_partition::<Invoice>(&2023).await?;
+                assert_eq!(invoices.len(), 2);
+                assert!(invoices.contains_key(&4));
+                assert!(invoices.contains_key(&5));
+
+                // the partition that was not loaded reads the committed rows on its first access.
+                let invoices = ctx.tbl_of_partition::<Invoice>(&2024).await?;
+                assert_eq!(invoices.len(), 3);
+                assert!(invoices.contains_key(&2));
+            }
+
+            // the partitions are kept while they are accessed between two gc.
+            ctx.gc();
+            assert_eq!(ctx.tbl_of::<Invoice>().await?.loaded_len(), 2);
+
+            ctx.tbl_of_partition::<Invoice>(&2024).await?;
+            ctx.gc();
+
+            let tbl = ctx.tbl_of::<Invoice>().await?;
+            assert!(!tbl.is_loaded(&2023));
+            assert!(tbl.is_loaded(&2024));
+
+            // the rows are found through the partition they were moved to.
+            assert!(tbl.get(&4).is_none());
+            assert_eq!(tbl.get(&2), Some(&Invoice { id: 2, year: 2024 }));
+
+            Ok(())
+        },
+        "load_partitions_on_demand",
+    )
+    .await
+}
+
+#[tokio::test]
+async fn remove_row_of_unloaded_partition() -> Result<()> {
+    async_cell_lock::with_deadlock_check(
+        async move {
+            let factory = MemFactory::new();
+            seed(&factory).await?;
+
+            let ctx = create_ctx(&factory);
+
+            {
+                let ctx = ctx.queue().await?;
+                let mut trx = ctx.transaction(Uuid::nil());
+
+                // no partition is loaded, the row is read from the provider.
+                assert!(trx.remove::<Invoice>(2).await?);
+
+                let log = trx.commit().await?;
+                ctx.write().await?.apply_log(log);
+            }
+
+            let ctx = create_ctx(&factory);
+            let ctx = ctx.read().await?;
+            let invoices = ctx.tbl_of_partition::<Invoice>(&2023).await?;
+
+            assert_eq!(invoices.len(), 1);
+            assert!(!invoices.contains_key(&2));
+
+            Ok(())
+        },
+        "remove_row_of_unloaded_partition",
+    )
+    .await
+}
+
+#[derive(Clone, Ctx, Debug, MemDelete, MemLoad, MemSave, PartialEq)]
+#[storm(collection = "partitioned_table")]
+struct Invoice {
+    id: u32,
+    year: u32,
+}
+
+impl Entity for Invoice {
+    type Key = u32;
+}
+
+impl PartitionedEntity for Invoice {
+    type Partition = u32;
+    type Filter = ();
+
+    fn partition(&self) -> u32 {
+        self.year
+    }
+
+    fn partition_filter(_year: &u32) -> Self::Filter {}
+}
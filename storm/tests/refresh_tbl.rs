@@ -0,0 +1,73 @@
+#![allow(clippy::unwrap_used)]
+
+use storm::{MemDelete, MemLoad, MemSave, Result, prelude::*, provider::MemFactory};
+use uuid::Uuid;
+
+fn create_ctx(factory: &MemFactory) -> QueueRwLock<Ctx> {
+    let mut provider = ProviderContainer::new();
+    provider.register("", factory.clone());
+    QueueRwLock::new(provider.into(), "ctx")
+}
+
+#[tokio::test]
+async fn refresh_applies_external_changes() -> Result<()> {
+    async_cell_lock::with_deadlock_check(
+        async move {
+            let factory = MemFactory::new();
+            let ctx = create_ctx(&factory);
+            let other = create_ctx(&factory);
+
+            {
+                let other = other.queue().await?;
+                let mut trx = other.transaction(Uuid::nil());
+                let mut tbl = trx.tbl_of::<Entity1>().await?;
+
+                tbl.insert(1, Entity1 { name: "a".into() }).await?;
+                tbl.insert(2, Entity1 { name: "b".into() }).await?;
+
+                let log = trx.commit().await?;
+                other.write().await?.apply_log(log);
+            }
+
+            let mut ctx = ctx.write().await?;
+
+            assert!(!ctx.refresh_tbl_of::<Entity1>().await?, "not loaded");
+            assert_eq!(ctx.tbl_of::<Entity1>().await?.len(), 2);
+            assert!(!ctx.refresh_tbl_of::<Entity1>().await?, "no changes");
+
+            {
+                let other = other.queue().await?;
+                let mut trx = other.transaction(Uuid::nil());
+                let mut tbl = trx.tbl_of::<Entity1>().await?;
+
+                tbl.insert(1, Entity1 { name: "c".into() }).await?;
+                tbl.remove(2).await?;
+                tbl.insert(3, Entity1 { name: "d".into() }).await?;
+
+                let log = trx.commit().await?;
+                other.write().await?.apply_log(log);
+            }
+
+            assert!(ctx.refresh_tbl_of::<Entity1>().await?);
+
+            let tbl = ctx.tbl_of::<Entity1>().await?;
+
+            assert_eq!(tbl.get(&1).unwrap().name, "c");
+            assert!(tbl.get(&2).is_none());
+            assert_eq!(tbl.get(&3).unwrap().name, "d");
+
+            Ok(())
+        },
+        "refresh_applies_external_changes",
+    )
+    .await
+}
+
+#[derive(Clone, Ctx, Debug, MemDelete, MemLoad, MemSave, PartialEq)]
+struct Entity1 {
+    name: String,
+}
+
+impl Entity for Entity1 {
+    type Key = u32;
+}
@@ -0,0 +1,78 @@
+#![allow(clippy::unwrap_used)]
+
+use std::sync::atomic::{AtomicUsize, Ordering::Relaxed};
+use storm::{
+    EntityAccessor, MemDelete, MemLoad, MemSave, Result, prelude::*, provider::MemFactory,
+};
+use uuid::Uuid;
+
+static REMOVING: AtomicUsize = AtomicUsize::new(0);
+static REMOVED: AtomicUsize = AtomicUsize::new(0);
+
+fn create_ctx() -> QueueRwLock<Ctx> {
+    let mut provider = ProviderContainer::new();
+    provider.register("", MemFactory::new());
+    QueueRwLock::new(provider.into(), "ctx")
+}
+
+#[tokio::test]
+async fn remove_all_fires_events_per_key() -> Result<()> {
+    async_cell_lock::with_deadlock_check(
+        async move {
+            let ctx = create_ctx();
+            let ctx = ctx.queue().await?;
+            let mut trx = ctx.transaction(Uuid::nil());
+            let mut tbl = trx.tbl_of::<Entity1>().await?;
+
+            tbl.insert_all((1..=3).map(|id| {
+                (
+                    id,
+                    Entity1 {
+                        name: id.to_string(),
+                    },
+                )
+            }))
+            .await?;
+
+            assert_eq!(tbl.remove_all([1, 2, 4]).await?, 2);
+            assert_eq!(REMOVING.load(Relaxed), 2);
+            assert_eq!(REMOVED.load(Relaxed), 2);
+
+            let log = trx.commit().await?;
+            let mut ctx = ctx.write().await?;
+
+            ctx.apply_log(log);
+
+            let tbl = ctx.tbl_of::<Entity1>().await?;
+
+            assert_eq!(tbl.len(), 1);
+            assert!(tbl.get(&3).is_some());
+
+            Ok(())
+        },
+        "remove_all_fires_events_per_key",
+    )
+    .await
+}
+
+#[storm::register]
+fn register_events() {
+    Entity1::removing().on(|_, _| {
+        REMOVING.fetch_add(1, Relaxed);
+        Box::pin(async { Ok(()) })
+    });
+
+    Entity1::removed().on(|_, _, _| {
+        REMOVED.fetch_add(1, Relaxed);
+        Box::pin(async { Ok(()) })
+    });
+}
+
+#[derive(Clone, Ctx, Debug, MemDelete, MemLoad, MemSave, PartialEq)]
+struct Entity1 {
+    name: String,
+}
+
+impl Entity for Entity1 {
+    type Key = u32;
+}
@@ -0,0 +1,179 @@
+#![allow(clippy::unwrap_used)]
+
+use std::{
+    sync::atomic::{AtomicU32, Ordering::Relaxed},
+    time::Duration,
+};
+use storm::{
+    BoxFuture, Error, MemDelete, MemLoad, MemSave, Result, RetryOptions,
+    prelude::*,
+    provider::{CommitPhase, MemFactory},
+    retry_transaction,
+};
+use uuid::Uuid;
+
+fn create_ctx(factory: &MemFactory) -> QueueRwLock<Ctx> {
+    let mut provider = ProviderContainer::new();
+    provider.register("", factory.clone());
+    QueueRwLock::new(provider.into(), "ctx")
+}
+
+fn options() -> RetryOptions {
+    RetryOptions {
+        max_attempts: 3,
+        backoff: Duration::from_millis(1),
+        max_backoff: Duration::from_millis(2),
+    }
+}
+
+#[tokio::test]
+async fn retry_on_transient_error() -> Result<()> {
+    async_cell_lock::with_deadlock_check(
+        async move {
+            let attempts = AtomicU32::new(0);
+            let factory = MemFactory::new();
+            let ctx = create_ctx(&factory);
+            let ctx = ctx.queue().await?;
+
+            let logs = retry_transaction(&ctx, &options(), |ctx| {
+                let attempt = attempts.fetch_add(1, Relaxed) + 1;
+
+                Box::pin(async move {
+                    let mut trx = ctx.transaction(Uuid::nil());
+                    trx.insert(attempt, User { name: "a".into() }).await?;
+
+                    if attempt < 3 {
+                        return Err(Error::Deadlock);
+                    }
+
+                    trx.commit().await
+                })
+            })
+            .await?;
+
+            assert_eq!(attempts.into_inner(), 3);
+
+            let mut ctx = ctx.write().await?;
+            ctx.apply_log(logs);
+
+            // only the last attempt is committed.
+            let tbl = ctx.tbl_of::<User>().await?;
+            assert_eq!(tbl.len(), 1);
+            assert!(tbl.contains_key(&3));
+
+            Ok(())
+        },
+        "retry_on_transient_error",
+    )
+    .await
+}
+
+#[tokio::test]
+async fn no_retry_on_other_error() -> Result<()> {
+    async_cell_lock::with_deadlock_check(
+        async move {
+            let mut attempts = 0;
+            let factory = MemFactory::new();
+            let ctx = create_ctx(&factory);
+            let ctx = ctx.queue().await?;
+
+            let r = retry_transaction(&ctx, &options(), |_| -> BoxFuture<Result<()>> {
+                attempts += 1;
+                Box::pin(async { Err(Error::EntityNotFound) })
+            })
+            .await;
+
+            assert!(matches!(r, Err(Error::EntityNotFound)));
+            assert_eq!(attempts, 1);
+
+            Ok(())
+        },
+        "no_retry_on_other_error",
+    )
+    .await
+}
+
+#[tokio::test]
+async fn retry_on_timeout() -> Result<()> {
+    async_cell_lock::with_deadlock_check(
+        async move {
+            let mut attempts = 0;
+            let factory = MemFactory::new();
+            let ctx = create_ctx(&factory);
+            let ctx = ctx.queue().await?;
+
+            let r = retry_transaction(&ctx, &options(), |_| -> BoxFuture<Result<()>> {
+                attempts += 1;
+                Box::pin(async { Err(Error::Timeout) })
+            })
+            .await;
+
+            assert!(matches!(r, Err(Error::Timeout)));
+            assert_eq!(attempts, 3);
+
+            Ok(())
+        },
+        "retry_on_timeout",
+    )
+    .await
+}
+
+#[tokio::test]
+async fn no_retry_after_partial_commit() -> Result<()> {
+    async_cell_lock::with_deadlock_check(
+        async move {
+            let mut attempts = 0;
+            let factory = MemFactory::new();
+            let ctx = create_ctx(&factory);
+            let ctx = ctx.queue().await?;
+
+            let r = retry_transaction(&ctx, &options(), |_| -> BoxFuture<Result<()>> {
+                attempts += 1;
+
+                Box::pin(async {
+                    Err(Error::TransactionCommit {
+                        committed: vec!["a".into()],
+                        error: Box::new(Error::Deadlock),
+                        phase: CommitPhase::Commit,
+                        provider: "b".into(),
+                    })
+                })
+            })
+            .await;
+
+            assert!(matches!(r, Err(Error::TransactionCommit { .. })));
+            assert_eq!(attempts, 1);
+
+            // nothing is committed yet in the prepare phase.
+            let r = retry_transaction(&ctx, &options(), |_| -> BoxFuture<Result<()>> {
+                attempts += 1;
+
+                Box::pin(async {
+                    Err(Error::TransactionCommit {
+                        committed: Vec::new(),
+                        error: Box::new(Error::Deadlock),
+                        phase: CommitPhase::Prepare,
+                        provider: "b".into(),
+                    })
+                })
+            })
+            .await;
+
+            assert!(matches!(r, Err(Error::TransactionCommit { .. })));
+            assert_eq!(attempts, 4);
+
+            Ok(())
+        },
+        "no_retry_after_partial_commit",
+    )
+    .await
+}
+
+#[derive(Clone, Ctx, Debug, MemDelete, MemLoad, MemSave, PartialEq)]
+struct User {
+    name: String,
+}
+
+impl Entity for User {
+    type Key = u32;
+}
@@ -0,0 +1,93 @@
+#![allow(clippy::unwrap_used)]
+
+use storm::{
+    Error, MemDelete, MemLoad, MemSave, Result,
+    prelude::*,
+    provider::{LoadOne, MemFactory},
+};
+use uuid::Uuid;
+
+fn create_ctx() -> QueueRwLock<Ctx> {
+    let mut provider = ProviderContainer::new();
+    provider.register("", MemFactory::new());
+    QueueRwLock::new(provider.into(), "ctx")
+}
+
+#[tokio::test]
+async fn rollback_to_savepoint() -> Result<()> {
+    async_cell_lock::with_deadlock_check(
+        async move {
+            let ctx = create_ctx();
+            let ctx = ctx.queue().await?;
+            let mut trx = ctx.transaction(Uuid::nil());
+
+            trx.tbl_of::<Entity1>()
+                .await?
+                .insert(1, Entity1 { name: "a".into() })
+                .await?;
+
+            let savepoint = trx.savepoint().await?;
+            let mut tbl = trx.tbl_of::<Entity1>().await?;
+
+            tbl.insert(2, Entity1 { name: "b".into() }).await?;
+            tbl.remove(1).await?;
+
+            trx.rollback_to(savepoint).await?;
+
+            assert_eq!(trx.get_entity::<Entity1>(&1).await?.unwrap().name, "a");
+            assert!(trx.get_entity::<Entity1>(&2).await?.is_none());
+
+            let log = trx.commit().await?;
+
+            let mut ctx = ctx.write().await?;
+
+            ctx.apply_log(log);
+
+            let v: Option<Entity1> = ctx.provider().load_one(&1).await?;
+            assert_eq!(v.unwrap().name, "a");
+
+            let v: Option<Entity1> = ctx.provider().load_one(&2).await?;
+            assert!(v.is_none());
+
+            Ok(())
+        },
+        "rollback_to_savepoint",
+    )
+    .await
+}
+
+#[tokio::test]
+async fn rollback_releases_later_savepoints() -> Result<()> {
+    async_cell_lock::with_deadlock_check(
+        async move {
+            let ctx = create_ctx();
+            let ctx = ctx.queue().await?;
+            let mut trx = ctx.transaction(Uuid::nil());
+
+            trx.tbl_of::<Entity1>().await?;
+
+            let first = trx.savepoint().await?;
+            let second = trx.savepoint().await?;
+
+            trx.rollback_to(first).await?;
+
+            assert!(matches!(
+                trx.rollback_to(second).await,
+                Err(Error::SavepointNotFound)
+            ));
+
+            Ok(())
+        },
+        "rollback_releases_later_savepoints",
+    )
+    .await
+}
+
+#[derive(Clone, Ctx, Debug, MemDelete, MemLoad, MemSave, PartialEq)]
+struct Entity1 {
+    name: String,
+}
+
+impl Entity for Entity1 {
+    type Key = u32;
+}
@@ -0,0 +1,57 @@
+#![allow(clippy::unwrap_used)]
+
+use serde::{Deserialize, Serialize};
+use storm::{
+    MemDelete, MemLoad, MemSave, Result, SerializedLogs, prelude::*, provider::MemFactory,
+};
+use uuid::Uuid;
+
+fn create_ctx() -> QueueRwLock<Ctx> {
+    let mut provider = ProviderContainer::new();
+    provider.register("", MemFactory::new());
+    QueueRwLock::new(provider.into(), "ctx")
+}
+
+#[tokio::test]
+async fn apply_in_other_ctx() -> Result<()> {
+    async_cell_lock::with_deadlock_check(
+        async move {
+            let source = create_ctx();
+            let target = create_ctx();
+
+            target.read().await?.tbl_of::<Entity1>().await?;
+
+            let source = source.queue().await?;
+            let mut trx = source.transaction(Uuid::nil());
+            let mut tbl = trx.tbl_of::<Entity1>().await?;
+
+            tbl.insert(1, Entity1 { name: "a".into() }).await?;
+
+            let logs = trx.commit().await?;
+            let json = serde_json::to_string(&SerializedLogs::from_logs(&logs)?).unwrap();
+
+            source.write().await?.apply_log(logs);
+
+            let logs: SerializedLogs = serde_json::from_str(&json).unwrap();
+            let mut target = target.write().await?;
+
+            assert!(target.apply_serialized_log(logs)?);
+
+            let tbl = target.tbl_of::<Entity1>().await?;
+            assert_eq!(tbl.get(&1).unwrap().name, "a");
+
+            Ok(())
+        },
+        "apply_in_other_ctx",
+    )
+    .await
+}
+
+#[derive(Clone, Ctx, Debug, Deserialize, MemDelete, MemLoad, MemSave, PartialEq, Serialize)]
+struct Entity1 {
+    name: String,
+}
+
+impl Entity for Entity1 {
+    type Key = u32;
+}
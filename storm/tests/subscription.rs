@@ -0,0 +1,63 @@
+#![allow(clippy::unwrap_used)]
+
+use storm::{Change, MemDelete, MemLoad, MemSave, Result, prelude::*, provider::MemFactory};
+use uuid::Uuid;
+
+fn create_ctx() -> QueueRwLock<Ctx> {
+    let mut provider = ProviderContainer::new();
+    provider.register("", MemFactory::new());
+    QueueRwLock::new(provider.into(), "ctx")
+}
+
+#[tokio::test]
+async fn receive_applied_changes() -> Result<()> {
+    async_cell_lock::with_deadlock_check(
+        async move {
+            let ctx = create_ctx();
+            let mut changes = ctx.read().await?.subscribe::<Entity1>();
+
+            for remove in [false, true] {
+                let ctx = ctx.queue().await?;
+                let mut trx = ctx.transaction(Uuid::nil());
+                let mut tbl = trx.tbl_of::<Entity1>().await?;
+
+                match remove {
+                    true => tbl.remove(1).await?,
+                    false => tbl.insert(1, Entity1 { name: "a".into() }).await?,
+                };
+
+                let log = trx.commit().await?;
+                ctx.write().await?.apply_log(log);
+            }
+
+            let expected = Change {
+                key: 1,
+                old: false,
+                new: true,
+            };
+
+            assert_eq!(changes.recv().await.unwrap(), expected);
+
+            let expected = Change {
+                key: 1,
+                old: true,
+                new: false,
+            };
+
+            assert_eq!(changes.recv().await.unwrap(), expected);
+
+            Ok(())
+        },
+        "receive_applied_changes",
+    )
+    .await
+}
+
+#[derive(Clone, Ctx, Debug, MemDelete, MemLoad, MemSave, PartialEq)]
+struct Entity1 {
+    name: String,
+}
+
+impl Entity for Entity1 {
+    type Key = u32;
+}
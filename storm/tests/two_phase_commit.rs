@@ -0,0 +1,221 @@
+#![allow(clippy::unwrap_used)]
+
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+use storm::{
+    BoxFuture, Error, MemDelete, MemLoad, MemSave, Result, SerializedLogs,
+    prelude::*,
+    provider::{
+        CommitPhase, CommitRecord, LoadOne, MemFactory, Provider, ProviderFactory, RecoveryLog,
+    },
+};
+use uuid::Uuid;
+
+fn create_ctx() -> QueueRwLock<Ctx> {
+    let mut provider = ProviderContainer::new();
+    provider.register("", MemFactory::new());
+    provider.register("failing", FailingFactory(CommitPhase::Prepare));
+    QueueRwLock::new(provider.into(), "ctx")
+}
+
+#[tokio::test]
+async fn prepare_failure_cancels_all() -> Result<()> {
+    async_cell_lock::with_deadlock_check(
+        async move {
+            let ctx = create_ctx();
+            let ctx = ctx.queue().await?;
+
+            // the provider must be in use to take part in the commit.
+            ctx.provider().provide::<FailingProvider>("failing").await?;
+
+            let mut trx = ctx.transaction(Uuid::nil());
+            let mut tbl = trx.tbl_of::<Entity1>().await?;
+
+            tbl.insert(1, Entity1 { name: "a".into() }).await?;
+
+            match trx.commit().await {
+                Err(Error::TransactionCommit {
+                    committed,
+                    phase,
+                    provider,
+                    ..
+                }) => {
+                    assert_eq!(phase, CommitPhase::Prepare);
+                    assert_eq!(&*provider, "failing");
+                    assert!(committed.is_empty());
+                }
+                _ => panic!("prepare must fail"),
+            }
+
+            let v: Option<Entity1> = ctx.provider().load_one(&1).await?;
+            assert!(v.is_none());
+
+            Ok(())
+        },
+        "prepare_failure_cancels_all",
+    )
+    .await
+}
+
+#[tokio::test]
+async fn commit_failure_is_written_to_recovery_log() -> Result<()> {
+    async_cell_lock::with_deadlock_check(
+        async move {
+            let log = RecordingLog::default();
+            let mut provider = ProviderContainer::new();
+            provider.register("", MemFactory::new());
+            provider.register("failing", FailingFactory(CommitPhase::Commit));
+            provider.set_recovery_log(log.clone());
+
+            let ctx = QueueRwLock::new(provider.into(), "ctx");
+            let ctx = ctx.queue().await?;
+
+            ctx.provider().provide::<FailingProvider>("failing").await?;
+
+            let mut trx = ctx.transaction(Uuid::nil());
+            let mut tbl = trx.tbl_of::<Entity1>().await?;
+
+            tbl.insert(1, Entity1 { name: "a".into() }).await?;
+
+            assert!(trx.commit().await.is_err());
+
+            let records = log.0.lock().unwrap();
+            let [(prepared, first), (finished, last)] = &records[..] else {
+                panic!("expected a prepared and a finished record");
+            };
+
+            assert!(*prepared);
+            assert!(!*finished);
+            assert_eq!(first.id, last.id);
+            assert!(first.committed.is_empty());
+            assert_eq!(first.pending.len(), 2);
+            assert!(last.pending.iter().any(|p| &**p == "failing"));
+            assert_eq!(last.committed.len() + last.pending.len(), 2);
+
+            Ok(())
+        },
+        "commit_failure_is_written_to_recovery_log",
+    )
+    .await
+}
+
+#[tokio::test]
+async fn recover_replays_pending_commit() -> Result<()> {
+    async_cell_lock::with_deadlock_check(
+        async move {
+            let ctx = create_ctx();
+            let ctx = ctx.queue().await?;
+
+            let mut trx = ctx.transaction(Uuid::nil());
+            let mut tbl = trx.tbl_of::<Entity1>().await?;
+
+            tbl.insert(1, Entity1 { name: "a".into() }).await?;
+
+            let logs = trx.commit().await?;
+
+            // the process stopped after committing the provider "failing".
+            let log = RecordingLog::default();
+            log.0.lock().unwrap().push((
+                true,
+                CommitRecord {
+                    id: 1,
+                    committed: vec!["failing".into()],
+                    logs: SerializedLogs::by_provider(&logs)?,
+                    pending: vec!["".into()],
+                },
+            ));
+
+            let mut provider = ProviderContainer::new();
+            provider.register("", MemFactory::new());
+            provider.set_recovery_log(log.clone());
+
+            assert_eq!(provider.recover().await?, 1);
+            assert!(log.pending().await?.is_empty());
+
+            let v: Option<Entity1> = provider.load_one(&1).await?;
+            assert_eq!(v, Some(Entity1 { name: "a".into() }));
+
+            // a second recovery has nothing to do.
+            assert_eq!(provider.recover().await?, 0);
+
+            Ok(())
+        },
+        "recover_replays_pending_commit",
+    )
+    .await
+}
+
+struct FailingFactory(CommitPhase);
+
+impl ProviderFactory for FailingFactory {
+    type Provider = FailingProvider;
+
+    fn create_provider(&self) -> BoxFuture<'_, Result<Self::Provider>> {
+        Box::pin(async { Ok(FailingProvider(self.0)) })
+    }
+}
+
+/// A provider failing in the given phase.
+struct FailingProvider(CommitPhase);
+
+impl FailingProvider {
+    fn result(&self, phase: CommitPhase) -> BoxFuture<'_, Result<()>> {
+        let result = match self.0 == phase {
+            true => Err(Error::Str("cannot commit")),
+            false => Ok(()),
+        };
+
+        Box::pin(std::future::ready(result))
+    }
+}
+
+impl Provider for FailingProvider {
+    fn cancel(&self) {}
+
+    fn commit(&self) -> BoxFuture<'_, Result<()>> {
+        self.result(CommitPhase::Commit)
+    }
+
+    fn prepare(&self) -> BoxFuture<'_, Result<()>> {
+        self.result(CommitPhase::Prepare)
+    }
+}
+
+/// Keeps the records written, `true` for a prepared record.
+#[derive(Clone, Default)]
+struct RecordingLog(Arc<Mutex<Vec<(bool, CommitRecord)>>>);
+
+impl RecoveryLog for RecordingLog {
+    fn prepared<'a>(&'a self, record: &'a CommitRecord) -> BoxFuture<'a, Result<()>> {
+        self.0.lock().unwrap().push((true, record.clone()));
+        Box::pin(std::future::ready(Ok(())))
+    }
+
+    fn finished<'a>(&'a self, record: &'a CommitRecord) -> BoxFuture<'a, Result<()>> {
+        self.0.lock().unwrap().push((false, record.clone()));
+        Box::pin(std::future::ready(Ok(())))
+    }
+
+    fn pending(&self) -> BoxFuture<'_, Result<Vec<CommitRecord>>> {
+        let mut last = BTreeMap::new();
+
+        for (_, record) in self.0.lock().unwrap().iter() {
+            last.insert(record.id, record.clone());
+        }
+
+        let pending = last.into_values().filter(|r| !r.pending.is_empty());
+        Box::pin(std::future::ready(Ok(pending.collect())))
+    }
+}
+
+#[derive(Clone, Ctx, Debug, Deserialize, MemDelete, MemLoad, MemSave, PartialEq, Serialize)]
+struct Entity1 {
+    name: String,
+}
+
+impl Entity for Entity1 {
+    type Key = u32;
+}
@@ -28,8 +28,33 @@ fn implement(input: &DeriveInput) -> Result<TokenStream, TokenStream> {
         entity.span(),
     );
 
-    let coll_ty = args.collection.ty(entity);
-    let (gc, gc_collect) = gc(input)?;
+    let coll_ty = args.collection.ty(entity, args.capacity);
+    let provider = LitStr::new(&args.provider, entity.span());
+    let (gc, gc_collect) = gc(input, &table_alias)?;
+
+    // the lazy and partitioned tables are never fully loaded, the rows are loaded on demand.
+    let lazy = if matches!(
+        args.collection,
+        Collection::LazyTable | Collection::PartitionedTable
+    ) {
+        quote! {
+            fn entity_of<'a>(ctx: &'a storm::Ctx, key: &'a Self::Key) -> storm::BoxFuture<'a, storm::Result<Option<&'a Self>>>
+            where
+                storm::ProviderContainer: storm::provider::LoadAll<Self, (), Self::Tbl>,
+            {
+                #table_alias::__entity_of(ctx, key)
+            }
+
+            fn tbl_from(ctx: &storm::Ctx) -> storm::BoxFuture<'_, storm::Result<&Self::Tbl>>
+            where
+                storm::ProviderContainer: storm::provider::LoadAll<Self, (), Self::Tbl>,
+            {
+                Box::pin(async move { Ok(#table_alias::__tbl_from(ctx)) })
+            }
+        }
+    } else {
+        quote!()
+    };
 
     Ok(quote! {
         #vis type #table_alias = #coll_ty;
@@ -37,6 +62,8 @@ fn implement(input: &DeriveInput) -> Result<TokenStream, TokenStream> {
         impl storm::EntityAccessor for #entity {
             type Tbl = #table_alias;
 
+            #lazy
+
             #[inline]
             fn applied() -> &'static storm::AppliedEvent<Self> {
                 static E: storm::AppliedEvent<#entity> = storm::AppliedEvent::new();
@@ -98,7 +125,19 @@ fn implement(input: &DeriveInput) -> Result<TokenStream, TokenStream> {
 
         #[storm::register]
         fn #init_tbl_fn() {
+            #[allow(unused_imports)]
+            use storm::{
+                __LogCodecNoSerde as _, __LogCodecSerde as _, __LogNoReplay as _,
+                __LogReplay as _, __SnapshotClone as _, __SnapshotNoClone as _,
+            };
+
             storm::__register_apply(#table_alias::__apply_log, storm::ApplyOrder::Table);
+            storm::__register_log_codec(
+                (&storm::__LogCodecProbe::<#entity>::new()).log_codec(),
+                #provider,
+                (&storm::__LogCodecProbe::<#entity>::new()).log_replay(),
+            );
+            storm::__register_snapshot((&storm::__SnapshotProbe::<#entity>::new()).snapshot_fn());
             #gc_collect
         }
 
@@ -106,7 +145,7 @@ fn implement(input: &DeriveInput) -> Result<TokenStream, TokenStream> {
     })
 }
 
-fn gc(input: &DeriveInput) -> Result<(TokenStream, TokenStream), TokenStream> {
+fn gc(input: &DeriveInput, table_alias: &Ident) -> Result<(TokenStream, TokenStream), TokenStream> {
     let fields = input.fields()?;
     let ident = &input.ident;
     let types = fields.iter().map(|f| &f.ty);
@@ -123,25 +162,34 @@ fn gc(input: &DeriveInput) -> Result<(TokenStream, TokenStream), TokenStream> {
             }
         },
         quote! {
-            if <#ident as storm::Gc>::SUPPORT_GC {
+            if <#table_alias as storm::Gc>::SUPPORT_GC {
                 storm::Ctx::on_gc_collect(<#ident as storm::EntityAccessor>::tbl_gc);
             }
         },
     ))
 }
 
+// the variant names are the values of the `collection` attribute.
+#[allow(clippy::enum_variant_names)]
 #[derive(Clone, Copy, Debug, Default, Eq, FromMeta, PartialEq)]
 enum Collection {
     HashTable,
+    LazyTable,
+    PartitionedTable,
 
     #[default]
     VecTable,
 }
 
 impl Collection {
-    fn ty(&self, entity: &Ident) -> TokenStream {
+    fn ty(&self, entity: &Ident, capacity: Option<usize>) -> TokenStream {
         match self {
             Self::HashTable => quote!(storm::HashTable<#entity>),
+            Self::LazyTable => match capacity {
+                Some(capacity) => quote!(storm::LazyTable<#entity, #capacity>),
+                None => quote!(storm::LazyTable<#entity>),
+            },
+            Self::PartitionedTable => quote!(storm::PartitionedTable<#entity>),
             Self::VecTable => quote!(storm::VecTable<#entity>),
         }
     }
@@ -150,6 +198,15 @@ impl Collection {
 #[derive(Debug, FromDeriveInput)]
 #[darling(attributes(storm), allow_unknown_fields)]
 struct TypeArgs {
+    /// The maximum number of rows kept by a lazy table.
+    #[darling(default)]
+    capacity: Option<usize>,
+
     #[darling(default)]
     collection: Collection,
+
+    /// The provider saving the entity, shared with the save derives, to split the serialized
+    /// logs by provider.
+    #[darling(default)]
+    provider: String,
 }
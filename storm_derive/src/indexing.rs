@@ -204,7 +204,7 @@ fn indexing_fn(f: &ItemFn) -> TokenStream {
                         return Ok(v);
                     }
 
-                    let _gate = self.provider().gate(#index_name_lit);
+                    let _gate = self.provider().gate(#index_name_lit).await;
 
                     Ok(ext.get(var).get_or_init(|| #get_or_init))
                 })
@@ -7,13 +7,14 @@ mod macros;
 
 mod ctx;
 mod derive_input_ext;
-#[cfg(feature = "mssql")]
+#[cfg(any(feature = "mssql", feature = "sqlite"))]
 mod errors;
 mod field_ext;
 mod flat_set_index;
 mod hash_flat_set_index;
 mod indexing;
 mod locks_await;
+mod mem;
 #[cfg(feature = "mssql")]
 mod mssql;
 mod noop;
@@ -21,21 +22,25 @@ mod one_index;
 mod register;
 mod rename_all;
 mod single_set;
-#[cfg(feature = "mssql")]
+#[cfg(any(feature = "mssql", feature = "sqlite"))]
+mod sql;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+#[cfg(any(feature = "mssql", feature = "sqlite"))]
 mod string_ext;
 mod token_stream_ext;
 mod tree_index;
 mod type_ext;
 
 use derive_input_ext::DeriveInputExt;
-#[cfg(feature = "mssql")]
+#[cfg(any(feature = "mssql", feature = "sqlite"))]
 use errors::Errors;
-#[cfg(feature = "mssql")]
+#[cfg(any(feature = "mssql", feature = "sqlite"))]
 use field_ext::FieldExt;
 use proc_macro::TokenStream;
-#[cfg(feature = "mssql")]
+#[cfg(any(feature = "mssql", feature = "sqlite"))]
 use rename_all::RenameAll;
-#[cfg(feature = "mssql")]
+#[cfg(any(feature = "mssql", feature = "sqlite"))]
 use string_ext::StringExt;
 use syn::{DeriveInput, Item, parse_macro_input};
 use type_ext::TypeExt;
@@ -70,6 +75,24 @@ pub fn locks_await(input: TokenStream) -> TokenStream {
     locks_await::locks_await(&input).into()
 }
 
+#[proc_macro_derive(MemDelete, attributes(storm))]
+pub fn mem_delete(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    mem::delete(&input).into()
+}
+
+#[proc_macro_derive(MemLoad, attributes(storm))]
+pub fn mem_load(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    mem::load(&input).into()
+}
+
+#[proc_macro_derive(MemSave, attributes(storm))]
+pub fn mem_save(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    mem::save(&input).into()
+}
+
 #[cfg(feature = "mssql")]
 #[proc_macro_derive(MssqlDelete, attributes(storm))]
 pub fn mssql_delete(input: TokenStream) -> TokenStream {
@@ -126,6 +149,27 @@ pub fn single_set(_attr: TokenStream, item: TokenStream) -> TokenStream {
     single_set::single_set(item).into()
 }
 
+#[cfg(feature = "sqlite")]
+#[proc_macro_derive(SqliteDelete, attributes(storm))]
+pub fn sqlite_delete(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    sqlite::delete(&input).into()
+}
+
+#[cfg(feature = "sqlite")]
+#[proc_macro_derive(SqliteLoad, attributes(storm))]
+pub fn sqlite_load(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    sqlite::load(&input).into()
+}
+
+#[cfg(feature = "sqlite")]
+#[proc_macro_derive(SqliteSave, attributes(storm))]
+pub fn sqlite_save(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    sqlite::save(&input).into()
+}
+
 #[proc_macro_attribute]
 pub fn tree_index(_attr: TokenStream, item: TokenStream) -> TokenStream {
     let item = parse_macro_input!(item as Item);
@@ -1,4 +1,4 @@
-#[cfg(feature = "mssql")]
+#[cfg(any(feature = "mssql", feature = "sqlite"))]
 macro_rules! continue_ts {
     ($v:expr, $errors:ident) => {
         match $v {
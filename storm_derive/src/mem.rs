@@ -0,0 +1,149 @@
+use darling::FromDeriveInput;
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{DeriveInput, LitStr};
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(attributes(storm), allow_unknown_fields)]
+struct TypeAttrs {
+    /// The name of the provider in the ProviderContainer.
+    #[darling(default)]
+    provider: String,
+
+    /// The key is assigned by the key generator of the MemFactory.
+    #[darling(default)]
+    identity: String,
+
+    #[darling(default)]
+    reload_on_upsert: bool,
+
+    #[darling(default)]
+    no_ctx: bool,
+}
+
+impl TypeAttrs {
+    fn provider(&self) -> LitStr {
+        LitStr::new(&self.provider, Span::call_site())
+    }
+}
+
+pub(crate) fn delete(input: &DeriveInput) -> TokenStream {
+    let ident = &input.ident;
+    let attrs = try_ts!(TypeAttrs::from_derive_input(input).map_err(|e| e.write_errors()));
+    let provider = attrs.provider();
+
+    let no_ctx = if attrs.no_ctx {
+        quote! {}
+    } else {
+        quote! { impl storm::EntityRemove for #ident {} }
+    };
+
+    quote! {
+        #no_ctx
+
+        impl storm::provider::Delete<#ident> for storm::provider::TransactionProvider<'_> {
+            fn delete<'a>(&'a self, k: &'a <#ident as storm::Entity>::Key) -> storm::BoxFuture<'a, storm::Result<()>> {
+                Box::pin(async move {
+                    let provider: &storm::provider::MemProvider = storm::tri!(self.container().provide(#provider).await);
+                    storm::provider::Delete::<#ident>::delete(provider, k).await
+                })
+            }
+        }
+    }
+}
+
+pub(crate) fn load(input: &DeriveInput) -> TokenStream {
+    let ident = &input.ident;
+    let attrs = try_ts!(TypeAttrs::from_derive_input(input).map_err(|e| e.write_errors()));
+    let provider = attrs.provider();
+
+    quote! {
+        impl<C> storm::provider::LoadAll<#ident, (), C> for storm::provider::ProviderContainer
+        where
+            C: Default + Extend<(<#ident as storm::Entity>::Key, #ident)> + Send + 'static,
+        {
+            fn load_all_with_args<'a>(&'a self, filter: &'a (), args: storm::provider::LoadArgs) -> storm::BoxFuture<'a, storm::Result<C>> {
+                Box::pin(async move {
+                    let provider: &storm::provider::MemProvider = storm::tri!(self.provide(#provider).await);
+                    storm::provider::LoadAll::<#ident, (), C>::load_all_with_args(provider, filter, args).await
+                })
+            }
+        }
+
+        impl storm::provider::LoadOne<#ident> for storm::provider::ProviderContainer {
+            fn load_one_with_args<'a>(&'a self, k: &'a <#ident as storm::Entity>::Key, args: storm::provider::LoadArgs) -> storm::BoxFuture<'a, storm::Result<Option<#ident>>> {
+                Box::pin(async move {
+                    let provider: &storm::provider::MemProvider = storm::tri!(self.provide(#provider).await);
+                    storm::provider::LoadOne::<#ident>::load_one_with_args(provider, k, args).await
+                })
+            }
+        }
+
+        impl storm::provider::LoadMany<#ident> for storm::provider::ProviderContainer {
+            fn load_many_with_args<'a>(&'a self, keys: &'a [<#ident as storm::Entity>::Key], args: storm::provider::LoadArgs) -> storm::BoxFuture<'a, storm::Result<storm::provider::LoadManyRows<#ident>>> {
+                Box::pin(async move {
+                    let provider: &storm::provider::MemProvider = storm::tri!(self.provide(#provider).await);
+                    storm::provider::LoadMany::<#ident>::load_many_with_args(provider, keys, args).await
+                })
+            }
+        }
+    }
+}
+
+pub(crate) fn save(input: &DeriveInput) -> TokenStream {
+    let ident = &input.ident;
+    let attrs = try_ts!(TypeAttrs::from_derive_input(input).map_err(|e| e.write_errors()));
+    let provider = attrs.provider();
+    let upsert_mut = attrs.reload_on_upsert || !attrs.identity.is_empty();
+
+    let reload_entity = if attrs.reload_on_upsert {
+        quote! {
+            *v = storm::tri!(storm::provider::LoadOne::<#ident>::load_one_with_args(self.container(), &k, storm::provider::LoadArgs { use_transaction: true }).await.and_then(|v| v.ok_or(storm::Error::EntityNotFound)));
+        }
+    } else {
+        quote!()
+    };
+
+    let upsert = if upsert_mut {
+        quote! {
+            impl storm::provider::UpsertMut<#ident> for storm::provider::TransactionProvider<'_> {
+                fn upsert_mut<'a>(&'a self, k: &'a mut <#ident as storm::Entity>::Key, v: &'a mut #ident) -> storm::BoxFuture<'a, storm::Result<()>> {
+                    Box::pin(async move {
+                        let provider: &storm::provider::MemProvider = storm::tri!(self.container().provide(#provider).await);
+                        storm::tri!(storm::provider::UpsertMut::<#ident>::upsert_mut(provider, k, v).await);
+                        #reload_entity
+                        Ok(())
+                    })
+                }
+            }
+        }
+    } else {
+        quote! {
+            impl storm::provider::Upsert<#ident> for storm::provider::TransactionProvider<'_> {
+                fn upsert<'a>(&'a self, k: &'a <#ident as storm::Entity>::Key, v: &'a #ident) -> storm::BoxFuture<'a, storm::Result<()>> {
+                    Box::pin(async move {
+                        let provider: &storm::provider::MemProvider = storm::tri!(self.container().provide(#provider).await);
+                        storm::provider::Upsert::<#ident>::upsert(provider, k, v).await
+                    })
+                }
+            }
+        }
+    };
+
+    let no_ctx = if attrs.no_ctx {
+        quote! {}
+    } else if upsert_mut {
+        quote! { impl storm::EntityUpsertMut for #ident {} }
+    } else {
+        quote! { impl storm::EntityUpsert for #ident {} }
+    };
+
+    quote! {
+        #upsert
+        #no_ctx
+
+        impl storm::EntityValidate for #ident {
+            fn entity_validate(&self, _error: &mut Option<storm::Error>) {}
+        }
+    }
+}
@@ -22,6 +22,34 @@ impl<'a, S> Delete<'a, S> {
     }
 }
 
+impl<S> Delete<'_, S>
+where
+    S: AttrsSelector,
+{
+    /// Deletes the rows of many keys with a set-based statement, only possible with a single
+    /// key column. The keys errors are not collected since the delete of a single key
+    /// already reports them.
+    pub fn many(&self) -> Option<TokenStream> {
+        let table = S::table(self.attrs);
+
+        if table.is_empty() {
+            return Some(quote!());
+        }
+
+        match S::keys(self.attrs, &mut Vec::new()).as_slice() {
+            [key] => {
+                let column = format!("[{key}]");
+                let table = table.as_str();
+
+                Some(quote! {
+                    storm::tri!(storm_mssql::delete_keys(provider, #table, #column, keys).await);
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
 impl<S> ToTokens for Delete<'_, S>
 where
     S: AttrsSelector,
@@ -1,8 +1,5 @@
-use super::{
-    attrs::{FieldAttrs, TypeAttrs, check_empty, check_required},
-    builders::SelectBuilder,
-    read_row,
-};
+use super::{attrs::check_empty, builders::SelectBuilder, read_row};
+use crate::sql::attrs::{FieldAttrs, TypeAttrs, check_required};
 use proc_macro2::{Span, TokenStream};
 use quote::{ToTokens, TokenStreamExt as _, quote};
 use syn::{Field, Ident, LitStr};
@@ -50,14 +47,14 @@ impl<'a> LoadFields<'a> {
     }
 }
 
-impl ToTokens for LoadFields<'_> {
-    fn to_tokens(&self, tokens: &mut TokenStream) {
-        let mut errors = Vec::new();
+impl LoadFields<'_> {
+    /// Declares the `load_sql` statement and the `load_row` fn reading a row.
+    fn load_row(&self, errors: &mut Vec<TokenStream>) -> TokenStream {
         let mut select = self.select.clone();
 
-        check_required(&self.attrs.table, &mut errors);
+        check_required(&self.attrs.table, errors);
 
-        let keys = add_keys(self.attrs, &mut select, &mut errors);
+        let keys = add_keys(self.attrs, &mut select, errors);
         let sql = select.to_sql_lit(&self.attrs.table, &self.attrs.where_clause);
 
         let entity = self.entity;
@@ -72,7 +69,7 @@ impl ToTokens for LoadFields<'_> {
             Span::call_site(),
         );
 
-        tokens.append_all(quote! {
+        quote! {
             const SQL: &str = #sql;
 
             let load_sql = match sql.is_empty() {
@@ -86,6 +83,46 @@ impl ToTokens for LoadFields<'_> {
                     #entity { #fields }
                 ))
             }
+        }
+    }
+
+    /// Returns the rows as they are read instead of collecting them.
+    pub fn stream(&self) -> TokenStream {
+        let mut errors = Vec::new();
+        let load_row = self.load_row(&mut errors);
+        let provider = self.attrs.provider();
+
+        quote! {
+            #load_row
+            #(#errors)*
+            storm_mssql::_macro_load_stream(provider, #provider, load_sql, params, load_row, args.use_transaction)
+        }
+    }
+
+    /// Reads a page of rows, the `page` must be declared.
+    pub fn page(&self) -> TokenStream {
+        let mut errors = Vec::new();
+        let load_row = self.load_row(&mut errors);
+
+        quote! {
+            #load_row
+            #(#errors)*
+
+            let load_sql = page.order_sql(load_sql);
+            let rows: Vec<_> = storm::tri!(storm_mssql::QueryRows::query_rows(provider, load_sql, &*params, load_row, args.use_transaction).await);
+
+            Ok(page.page(rows))
+        }
+    }
+}
+
+impl ToTokens for LoadFields<'_> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let mut errors = Vec::new();
+        let load_row = self.load_row(&mut errors);
+
+        tokens.append_all(quote! {
+            #load_row
 
             let mut map: C = storm::tri!(storm_mssql::QueryRows::query_rows(provider, load_sql, &*params, load_row, args.use_transaction).await);
         });
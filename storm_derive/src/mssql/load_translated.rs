@@ -1,8 +1,9 @@
 use super::{
-    attrs::{TypeAttrs, check_empty, check_required},
+    attrs::check_empty,
     builders::{JoinBuilder, JoinConditions, SelectBuilder},
     read_row,
 };
+use crate::sql::attrs::{TypeAttrs, check_required};
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::{ToTokens, TokenStreamExt as _, quote};
 use syn::{Field, LitStr};
@@ -39,6 +40,10 @@ impl<'a> LoadTranslated<'a> {
         });
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
     pub fn to_where_clause(&self) -> TokenStream {
         let entity = &self.entity;
 
@@ -6,9 +6,13 @@ mod load_translated;
 mod save_translated;
 
 use crate::{
-    DeriveInputExt, Errors, FieldExt, RenameAll, StringExt, token_stream_ext::TokenStreamExt,
+    DeriveInputExt, Errors, FieldExt, RenameAll, StringExt,
+    sql::{
+        attrs::{FieldAttrs, TypeAttrs},
+        entity_validate, enum_fields_impl, is_translated,
+    },
+    token_stream_ext::TokenStreamExt,
 };
-use attrs::{FieldAttrs, TypeAttrs};
 use darling::{FromDeriveInput, FromField};
 use delete::Delete;
 use inflector::Inflector;
@@ -34,6 +38,23 @@ pub(crate) fn delete(input: &DeriveInput) -> TokenStream {
         quote! { impl storm::EntityRemove for #ident {} }
     };
 
+    // composite keys are deleted one by one.
+    let delete_many = match (translate.many(), normal.many()) {
+        (Some(translate), Some(normal)) => quote! {
+            fn delete_many<'a>(&'a self, keys: &'a [<#ident as storm::Entity>::Key]) -> storm::BoxFuture<'a, storm::Result<()>> {
+                storm_mssql::metrics_helper::delete_wrap(async move {
+                    let provider: &storm_mssql::MssqlProvider = storm::tri!(self.container().provide(#provider).await);
+
+                    #translate
+                    #normal
+
+                    Ok(())
+                }, #table_name)
+            }
+        },
+        _ => quote!(),
+    };
+
     quote! {
         #no_ctx
 
@@ -48,6 +69,8 @@ pub(crate) fn delete(input: &DeriveInput) -> TokenStream {
                     Ok(())
                 }, #table_name)
             }
+
+            #delete_many
         }
     }
 }
@@ -73,11 +96,18 @@ pub(crate) fn load(input: &DeriveInput) -> TokenStream {
     let enum_fields_ident = Ident::new(&format!("{ident}Fields"), ident.span());
     let mut max_lengths = Vec::new();
     let mut check_entity_fields = Vec::new();
+    let mut columns = Vec::new();
+    let mut partition = None;
+    let partition_field = attrs.partition.as_deref();
 
-    for key in attrs.keys(&mut errors) {
+    let keys = attrs.keys(&mut errors);
+
+    for key in &keys {
         filter_sql.add_filter(key);
     }
 
+    let change_tracking = change_tracking(ident, &attrs, &keys);
+
     for field in try_ts!(input.fields()) {
         let field_ident = continue_ts!(field.ident(), errors);
 
@@ -90,6 +120,10 @@ pub(crate) fn load(input: &DeriveInput) -> TokenStream {
 
         let column = continue_ts!(RenameAll::column(rename_all, &attrs.column, field), errors);
 
+        if partition_field.is_some_and(|p| field_ident == p) {
+            partition = Some(partition_impl(ident, field_ident, &field.ty, &column));
+        }
+
         if is_translated(&field.ty) {
             load.skip_field(field, &attrs, &mut errors);
             translated.add_field(field, &column);
@@ -100,6 +134,17 @@ pub(crate) fn load(input: &DeriveInput) -> TokenStream {
         } else {
             load.add_field(field, &attrs, &column);
 
+            if !attrs.skip_load() && !attrs.part && attrs.load_with.is_none() {
+                let ty = &field.ty;
+                let column = LitStr::new(&column, field_ident.span());
+                let max_length = LitInt::new(&attrs.max_length.to_string(), field_ident.span());
+
+                columns.push((
+                    quote!(pub #field_ident: storm_mssql::Column<#ty>,),
+                    quote!(#field_ident: storm_mssql::Column::new(#column).with_max_length(#max_length),),
+                ));
+            }
+
             if !attrs.skip_save() && !attrs.skip_diff() {
                 load_diff_field(&mut diff, field_ident, &enum_fields_ident);
             }
@@ -121,9 +166,74 @@ pub(crate) fn load(input: &DeriveInput) -> TokenStream {
         }
     }
 
+    if partition_field.is_some() && partition.is_none() {
+        errors.push(
+            Error::new(attrs.partition.span(), "Partition field not found.").to_compile_error(),
+        );
+    }
+
     try_ts!(errors.result());
 
     let translated_where = translated.to_where_clause();
+
+    // the translations are loaded by a second query, they cannot be streamed nor paged.
+    let load_stream = if translated.is_empty() {
+        let stream = load.stream();
+        let page = load.page();
+        let provider = attrs.provider();
+
+        let key_param = match keys.len() {
+            1 => quote!(|k| Some(k as _)),
+            _ => quote!(|_| None),
+        };
+
+        quote! {
+            impl<FILTER> storm_mssql::LoadPage<#ident, FILTER> for storm::provider::ProviderContainer
+            where
+                FILTER: storm_mssql::FilterSql,
+            {
+                fn load_page_with_args<'a>(
+                    &'a self,
+                    filter: &'a FILTER,
+                    order_by: &'a [storm_mssql::OrderBy],
+                    after: Option<&'a storm_mssql::PageToken<<#ident as storm::Entity>::Key>>,
+                    limit: usize,
+                    args: storm::provider::LoadArgs,
+                ) -> storm::BoxFuture<'a, storm::Result<storm_mssql::Page<<#ident as storm::Entity>::Key, #ident>>> {
+                    Box::pin(async move {
+                        let page = storm_mssql::_MacroPage {
+                            after,
+                            filter,
+                            key_param: #key_param,
+                            keys: &[#(#keys),*],
+                            limit,
+                            order_by,
+                        };
+
+                        storm::tri!(page.check_after());
+
+                        let (sql, params) = storm_mssql::FilterSql::filter_sql(&page, 0);
+                        let provider: &storm_mssql::MssqlProvider = storm::tri!(self.provide(#provider).await);
+                        #page
+                    })
+                }
+            }
+
+            impl<FILTER> storm::provider::LoadStream<#ident, FILTER> for storm::provider::ProviderContainer
+            where
+                FILTER: storm_mssql::FilterSql,
+            {
+                fn load_stream_with_args<'a>(&'a self, filter: &'a FILTER, args: storm::provider::LoadArgs) -> storm::BoxStream<'a, storm::Result<(<#ident as storm::Entity>::Key, #ident)>> {
+                    let (sql, params) = storm_mssql::FilterSql::filter_sql(filter, 0);
+                    let provider = self;
+                    #stream
+                }
+            }
+        }
+    } else {
+        quote!()
+    };
+    let load_one = load_one(ident, &keys, &filter_sql);
     let provider = attrs.provider();
     let diff = apply_entity_diff(diff, ident);
     let max_lengths = if max_lengths.is_empty() {
@@ -132,6 +242,32 @@ pub(crate) fn load(input: &DeriveInput) -> TokenStream {
         quote! { impl #ident { #(#max_lengths)* } }
     };
 
+    // a single key gets a column named after it, unless a field has the same name.
+    let key_column = match keys.as_slice() {
+        [key] => syn::parse_str::<Ident>(&key.to_snake_case())
+            .ok()
+            .map(|i| (i, *key)),
+        _ => None,
+    };
+
+    if let Some((key_ident, key)) = key_column
+        && !try_ts!(input.fields())
+            .iter()
+            .any(|f| f.ident.as_ref() == Some(&key_ident))
+    {
+        let column = LitStr::new(key, ident.span());
+
+        columns.insert(
+            0,
+            (
+                quote!(pub #key_ident: storm_mssql::Column<<#ident as storm::Entity>::Key>,),
+                quote!(#key_ident: storm_mssql::Column::new(#column),),
+            ),
+        );
+    }
+
+    let columns = columns_impl(&input.vis, ident, columns);
+
     let check_entity_fields = if check_entity_fields.is_empty() {
         quote! {}
     } else {
@@ -194,27 +330,120 @@ pub(crate) fn load(input: &DeriveInput) -> TokenStream {
             }
         }
 
-        impl storm::provider::LoadOne<#ident> for storm::provider::ProviderContainer {
-            fn load_one_with_args<'a>(&'a self, k: &'a <#ident as Entity>::Key, args: storm::provider::LoadArgs) -> storm::BoxFuture<'a, storm::Result<Option<#ident>>> {
-                Box::pin(async move {
-                    let filter = #filter_sql;
-                    let v: storm::provider::LoadOneInternal<#ident> = storm::tri!(storm::provider::LoadAll::load_all_with_args(self, &filter, args).await);
-                    Ok(v.into_inner())
-                })
-            }
-        }
+        #load_stream
+
+        #load_one
+        #partition
 
         impl storm_mssql::MssqlMeta for #ident {
             const TABLE: &'static str = #table_name;
             const TRANSLATED_TABLE: &'static str = #translated_table_name;
         }
 
+        #columns
+
+        #change_tracking
         #max_lengths
         #diff
         #test
     }
 }
 
+/// The partition of a row is a field, each partition being loaded with a filter on its column.
+fn partition_impl(ident: &Ident, field: &Ident, ty: &Type, column: &str) -> TokenStream {
+    let column = LitStr::new(&format!("t.[{column}]"), field.span());
+
+    quote! {
+        impl storm::PartitionedEntity for #ident {
+            type Partition = #ty;
+            type Filter = storm_mssql::PartitionFilter<#ty>;
+
+            fn partition(&self) -> Self::Partition {
+                self.#field.clone()
+            }
+
+            fn partition_filter(partition: &Self::Partition) -> Self::Filter {
+                storm_mssql::PartitionFilter {
+                    column: #column,
+                    value: partition.clone(),
+                }
+            }
+        }
+    }
+}
+
+/// With a single key, the loads of one key are coalesced into a set-based load of many keys.
+fn load_one(ident: &Ident, keys: &[&str], filter_sql: &FilterSqlImpl) -> TokenStream {
+    let [key] = keys else {
+        return quote! {
+            impl storm::provider::LoadOne<#ident> for storm::provider::ProviderContainer {
+                fn load_one_with_args<'a>(&'a self, k: &'a <#ident as Entity>::Key, args: storm::provider::LoadArgs) -> storm::BoxFuture<'a, storm::Result<Option<#ident>>> {
+                    Box::pin(async move {
+                        let filter = #filter_sql;
+                        let v: storm::provider::LoadOneInternal<#ident> = storm::tri!(storm::provider::LoadAll::load_all_with_args(self, &filter, args).await);
+                        Ok(v.into_inner())
+                    })
+                }
+            }
+
+            impl storm::provider::LoadMany<#ident> for storm::provider::ProviderContainer {}
+        };
+    };
+
+    let column = format!("t.[{key}]");
+
+    quote! {
+        impl storm::provider::LoadOne<#ident> for storm::provider::ProviderContainer {
+            fn load_one_with_args<'a>(&'a self, k: &'a <#ident as Entity>::Key, args: storm::provider::LoadArgs) -> storm::BoxFuture<'a, storm::Result<Option<#ident>>> {
+                self.load_one_coalesced(k, args)
+            }
+        }
+
+        impl storm::provider::LoadMany<#ident> for storm::provider::ProviderContainer {
+            fn load_many_with_args<'a>(&'a self, keys: &'a [<#ident as Entity>::Key], args: storm::provider::LoadArgs) -> storm::BoxFuture<'a, storm::Result<storm::provider::LoadManyRows<#ident>>> {
+                Box::pin(async move {
+                    let mut vec = Vec::with_capacity(keys.len());
+
+                    for keys in keys.chunks(storm_mssql::MAX_PARAMS) {
+                        let filter = storm_mssql::KeysFilter(#column, keys);
+                        let args = storm::provider::LoadArgs { use_transaction: args.use_transaction };
+                        let map: std::collections::HashMap<<#ident as Entity>::Key, #ident> = storm::tri!(storm::provider::LoadAll::load_all_with_args(self, &filter, args).await);
+                        vec.extend(map);
+                    }
+
+                    Ok(vec)
+                })
+            }
+        }
+    }
+}
+
+fn change_tracking(ident: &Ident, attrs: &TypeAttrs, keys: &[&str]) -> TokenStream {
+    if !attrs.change_tracking {
+        return quote!();
+    }
+
+    let provider = attrs.provider();
+    let read_keys = (0..keys.len()).map(read_row);
+
+    let read_key = if keys.len() == 1 {
+        quote!(#(#read_keys)*)
+    } else {
+        quote!((#(#read_keys,)*))
+    };
+
+    quote! {
+        impl storm_mssql::ChangeTracking for #ident {
+            const KEYS: &'static [&'static str] = &[#(#keys),*];
+            const PROVIDER: &'static str = #provider;
+
+            fn read_key(row: storm_mssql::tiberius::Row) -> storm::Result<<Self as storm::Entity>::Key> {
+                Ok(#read_key)
+            }
+        }
+    }
+}
+
 pub(crate) fn save(input: &DeriveInput) -> TokenStream {
     let ident = &input.ident;
     let attrs = try_ts!(TypeAttrs::from_derive_input(input).map_err(|e| e.write_errors()));
@@ -229,9 +458,13 @@ pub(crate) fn save(input: &DeriveInput) -> TokenStream {
     let mut translated_backup = Vec::new();
     let mut translated_restore = Vec::new();
     let is_identity_key = attrs.is_identity_key();
+    let is_generated_key = attrs.is_generated_key();
     let identity_col = attrs.identity.to_lowercase();
     let table_name = LitStr::new(&attrs.table, attrs.table.span());
     let mut enum_fields = Vec::new();
+    let mut output = Vec::new();
+    let mut output_fields = Vec::new();
+    let mut concurrency_check = quote!();
     let enum_fields_ident = Ident::new(&format!("{ident}Fields"), ident.span());
     let vis = &input.vis;
 
@@ -256,6 +489,49 @@ pub(crate) fn save(input: &DeriveInput) -> TokenStream {
 
         if is_identity {
             identity_found = true;
+        }
+
+        // the identity, generated and concurrency token columns are read back with the OUTPUT
+        // clause.
+        if (is_identity && !is_identity_key) || attrs.generated || attrs.concurrency_token {
+            let ident = continue_ts!(field.ident(), errors);
+            let name = LitStr::new(&format!("[{column}]"), ident.span());
+
+            output.push(if is_identity {
+                quote!(storm::tri!(builder.add_output_identity(#name, &v.#ident));)
+            } else if attrs.concurrency_token {
+                quote! {
+                    let token_defined = storm::IsDefined::is_defined(&v.#ident);
+                    builder.add_concurrency_token(#name, &v.#ident);
+                }
+            } else {
+                quote!(builder.add_output(#name);)
+            });
+
+            if attrs.concurrency_token {
+                if !concurrency_check.is_empty() {
+                    errors.push(
+                        Error::new(ident.span(), "Only one concurrency token is possible.")
+                            .to_compile_error(),
+                    );
+                }
+
+                // no row is updated when the token does not match.
+                concurrency_check = quote! {
+                    if token_defined && row.is_none() {
+                        return Err(storm::Error::ConcurrencyConflict {
+                            key: format!("{k:?}"),
+                            table: #table_name.into(),
+                        });
+                    }
+                };
+            }
+
+            output_fields.push(ident);
+            continue;
+        }
+
+        if is_identity {
             continue;
         }
 
@@ -356,7 +632,11 @@ pub(crate) fn save(input: &DeriveInput) -> TokenStream {
         );
     }
 
-    let add_key_or_identity = if is_identity_key {
+    let sequence = LitStr::new(&attrs.sequence, attrs.sequence.span());
+
+    let add_key_or_identity = if !attrs.sequence.is_empty() {
+        quote!(add_key_sequence)
+    } else if is_identity_key {
         quote!(add_key_identity)
     } else {
         quote!(add_key_ref)
@@ -370,20 +650,24 @@ pub(crate) fn save(input: &DeriveInput) -> TokenStream {
                 let n = LitInt::new(&index.to_string(), ident.span());
                 quote! { &k.#n }
             }
-            false if is_identity_key => quote! { *k },
+            false if is_generated_key => quote! { *k },
             false => quote! { k },
         };
 
-        wheres.push(quote!(builder.#add_key_or_identity(#name, #k);));
+        wheres.push(match attrs.sequence.is_empty() {
+            true => quote!(builder.#add_key_or_identity(#name, #k);),
+            false => quote!(builder.#add_key_or_identity(#name, #sequence, #k);),
+        });
     }
 
     try_ts!(errors.result());
 
+    let upsert_mut = attrs.reload_on_upsert_or_identity() || !output.is_empty();
     let upsert_trait;
     let upsert_sig;
     let entity_part_key;
 
-    if attrs.reload_on_upsert_or_identity() {
+    if upsert_mut {
         upsert_trait = quote!(storm::provider::UpsertMut<#ident>);
         upsert_sig = quote!(fn upsert_mut<'a>(&'a self, k: &'a mut <#ident as storm::Entity>::Key, v: &'a mut #ident) -> storm::BoxFuture<'a, storm::Result<()>>);
         entity_part_key = quote!(&k.clone());
@@ -393,7 +677,8 @@ pub(crate) fn save(input: &DeriveInput) -> TokenStream {
         entity_part_key = quote!(k);
     }
 
-    let reload_entity = if attrs.reload_on_upsert() {
+    // the identity fields are read with the OUTPUT clause instead of reloading the row.
+    let reload_entity = if attrs.reload_on_upsert {
         let backup = quote!(#(#translated_backup)*);
         let restore = quote!(#(#translated_restore)*);
 
@@ -406,23 +691,92 @@ pub(crate) fn save(input: &DeriveInput) -> TokenStream {
         quote!()
     };
 
-    let builder_invoke = if is_identity_key {
-        quote!(storm::tri!(builder.execute_identity(provider, k).await);)
+    let builder_invoke = match (is_generated_key, output.is_empty()) {
+        (true, true) => quote!(storm::tri!(builder.execute_identity(provider, k).await);),
+        (true, false) => {
+            quote!(let row = storm::tri!(builder.execute_identity_output(provider, k).await);)
+        }
+        (false, true) => quote!(storm::tri!(builder.execute(provider).await);),
+        (false, false) => quote!(let row = storm::tri!(builder.execute_output(provider).await);),
+    };
+
+    let read_output = if output.is_empty() {
+        quote!()
     } else {
-        quote!(storm::tri!(builder.execute(provider).await);)
+        let fields = output_fields.iter().enumerate().map(|(index, field)| {
+            let read = read_row(index);
+            quote!(v.#field = #read;)
+        });
+
+        quote! {
+            if let Some(row) = row {
+                #(#fields)*
+            }
+        }
     };
 
+    let output = output.ts();
     let save_part = save_part.ts();
     let wheres = wheres.ts();
     let table = LitStr::new(&attrs.table, ident.span());
     let provider = attrs.provider();
+
+    // the rows are sent in batches using a MERGE statement, the translations are saved after.
+    let upsert_all = if upsert_mut {
+        quote!()
+    } else {
+        let translated_all = if translated.is_empty() {
+            quote!()
+        } else {
+            quote! {
+                for (k, v) in rows {
+                    #translated
+                }
+            }
+        };
+
+        let batch_fn = |name: TokenStream, execute: TokenStream| {
+            quote! {
+                fn #name<'a>(&'a self, rows: &'a [(<#ident as storm::Entity>::Key, #ident)]) -> storm::BoxFuture<'a, storm::Result<()>> {
+                    storm_mssql::metrics_helper::upsert_wrap(async move {
+                        let provider: &storm_mssql::MssqlProvider = storm::tri!(self.container().provide(#provider).await);
+                        let mut merge = storm_mssql::MergeBuilder::new(#table);
+
+                        for (k, v) in rows {
+                            let mut builder = storm_mssql::UpsertBuilder::new(#table);
+
+                            storm_mssql::SaveEntityPart::save_entity_part(v, k, &mut builder);
+
+                            #wheres
+                            merge.add_row(builder);
+                        }
+
+                        storm::tri!(merge.#execute(provider).await);
+                        #translated_all
+
+                        Ok(())
+                    }, #table_name)
+                }
+            }
+        };
+
+        // the new rows use the bulk load protocol when there are enough of them.
+        let upsert_all = batch_fn(quote!(upsert_all), quote!(execute));
+        let insert_all = batch_fn(quote!(insert_all), quote!(execute_bulk));
+
+        quote! {
+            #upsert_all
+            #insert_all
+        }
+    };
+
     let diff = entity_diff(ident, diff);
     let enum_fields = enum_fields_impl(vis, ident, enum_fields, &enum_fields_ident);
     let entity_validate = entity_validate(entity_validations, ident);
 
     let no_ctx = if attrs.no_ctx {
         quote! {}
-    } else if attrs.reload_on_upsert_or_identity() {
+    } else if upsert_mut {
         quote! { impl storm::EntityUpsertMut for #ident {} }
     } else {
         quote! { impl storm::EntityUpsert for #ident {} }
@@ -439,13 +793,18 @@ pub(crate) fn save(input: &DeriveInput) -> TokenStream {
                     storm_mssql::SaveEntityPart::save_entity_part(v, entity_part_key, &mut builder);
 
                     #wheres
+                    #output
                     #builder_invoke
+                    #concurrency_check
+                    #read_output
                     #reload_entity
                     #translated
 
                     Ok(())
                 }, #table_name)
             }
+
+            #upsert_all
         }
 
         #diff
@@ -462,18 +821,6 @@ pub(crate) fn save(input: &DeriveInput) -> TokenStream {
     }
 }
 
-fn is_translated(t: &Type) -> bool {
-    match t {
-        Type::Path(p) => p
-            .path
-            .segments
-            .iter()
-            .next_back()
-            .is_some_and(|s| &s.ident == "Translated"),
-        _ => false,
-    }
-}
-
 /// Creates a where clauses and parameters for the load sql query.
 #[derive(Default)]
 struct FilterSqlImpl {
@@ -540,17 +887,6 @@ fn entity_diff(ident: &Ident, diff: Option<Vec<TokenStream>>) -> TokenStream {
     }
 }
 
-fn entity_validate(validations: Vec<TokenStream>, ident: &Ident) -> TokenStream {
-    quote! {
-        impl storm::EntityValidate for #ident {
-            #[allow(unused)]
-            fn entity_validate(&self, error: &mut Option<storm::Error>) {
-                #(#validations)*
-            }
-        }
-    }
-}
-
 fn load_diff_field(diff: &mut Option<Vec<TokenStream>>, field: &Ident, enum_fields_ident: &Ident) {
     if let Some(diff) = diff.as_mut() {
         let name = Ident::new(&field.to_string().to_pascal_case(), field.span());
@@ -563,75 +899,34 @@ fn read_row(column_index: usize) -> TokenStream {
     quote!(storm::tri!(storm_mssql::_macro_load_field(&row, #l)))
 }
 
-fn enum_fields_impl(
+/// Creates the `COLUMNS` const of the entity, describing each loaded column.
+fn columns_impl(
     vis: &Visibility,
     ident: &Ident,
-    fields: Vec<Ident>,
-    enum_ident: &Ident,
+    columns: Vec<(TokenStream, TokenStream)>,
 ) -> TokenStream {
-    if fields.is_empty() {
+    if columns.is_empty() {
         return quote!();
     }
 
-    let names = fields
-        .iter()
-        .map(|i| LitStr::new(&i.to_string().to_camel_case(), i.span()));
+    let columns_ident = Ident::new(&format!("{ident}Columns"), ident.span());
+    let doc = LitStr::new(
+        &format!("The key and loaded columns of [{ident}], see [{ident}::COLUMNS]."),
+        ident.span(),
+    );
+    let (fields, values): (Vec<_>, Vec<_>) = columns.into_iter().unzip();
 
     quote! {
-        #[derive(Clone, Copy, Eq, serde::Deserialize)]
-        #[serde(rename_all = "camelCase")]
-        #vis enum #enum_ident {
-            #(#fields,)*
-        }
-
-        impl AsRef<str> for #enum_ident {
-            fn as_ref(&self) -> &str {
-                match self {
-                    #(Self::#fields => #names,)*
-                }
-            }
-        }
-
-        impl std::fmt::Debug for #enum_ident {
-            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-                f.write_str(self.as_ref())
-            }
+        #[doc = #doc]
+        #[derive(Clone, Copy)]
+        #vis struct #columns_ident {
+            #(#fields)*
         }
 
-        impl std::fmt::Display for #enum_ident {
-            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-                f.write_str(self.as_ref())
-            }
-        }
-
-        impl std::hash::Hash for #enum_ident {
-            fn hash<H>(&self, state: &mut H)
-            where
-                H: std::hash::Hasher
-            {
-                (*self as u16).hash(state);
-            }
-        }
-
-        impl std::cmp::PartialEq for #enum_ident {
-            fn eq(&self, other: &Self) -> bool {
-                *self as u16 == *other as u16
-            }
-        }
-
-        impl serde::Serialize for #enum_ident {
-            fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
-            where
-                S: serde::Serializer
-            {
-                self.as_ref().serialize(serializer)
-            }
-        }
-
-        impl storm::Fields for #enum_ident {}
-
-        impl storm::EntityFields for #ident {
-            type Fields = #enum_ident;
+        impl #ident {
+            pub const COLUMNS: #columns_ident = #columns_ident {
+                #(#values)*
+            };
         }
     }
 }
@@ -1,8 +1,9 @@
 use super::{
     TypeAttrs,
-    attrs::{check_empty, check_required},
+    attrs::check_empty,
     builders::{ParamsBuilder, UpsertBuilder},
 };
+use crate::sql::attrs::check_required;
 use proc_macro2::{Span, TokenStream};
 use quote::{ToTokens, TokenStreamExt as _, quote};
 use syn::{Field, LitInt};
@@ -22,6 +23,10 @@ impl<'a> SaveTranslated<'a> {
         }
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.params.is_empty()
+    }
+
     pub fn add_field(&mut self, field: &Field, column: &str) {
         let ident = &field.ident;
         let param_index = self.params.add_ts(quote!(&v.#ident.get(culture) as _));
@@ -35,6 +35,8 @@ pub(crate) fn load(input: &DeriveInput) -> TokenStream {
                 Box::pin(async { Ok(None) })
             }
         }
+
+        impl storm::provider::LoadMany<#ident> for storm::provider::ProviderContainer {}
     }
 }
 
@@ -1,12 +1,12 @@
 use darling::FromMeta;
 
-#[cfg(feature = "mssql")]
+#[cfg(any(feature = "mssql", feature = "sqlite"))]
 use inflector::Inflector;
 
-#[cfg(feature = "mssql")]
+#[cfg(any(feature = "mssql", feature = "sqlite"))]
 use proc_macro2::TokenStream;
 
-#[cfg(feature = "mssql")]
+#[cfg(any(feature = "mssql", feature = "sqlite"))]
 use syn::{Field, spanned::Spanned};
 
 #[allow(clippy::enum_variant_names)]
@@ -23,7 +23,7 @@ pub(crate) enum RenameAll {
 }
 
 impl RenameAll {
-    #[cfg(feature = "mssql")]
+    #[cfg(any(feature = "mssql", feature = "sqlite"))]
     pub fn column(
         this: Option<Self>,
         column: &Option<String>,
@@ -48,7 +48,7 @@ impl RenameAll {
         })
     }
 
-    #[cfg(feature = "mssql")]
+    #[cfg(any(feature = "mssql", feature = "sqlite"))]
     fn rename(&self, s: String) -> String {
         match self {
             Self::CamelCase => s.to_camel_case(),
@@ -0,0 +1,102 @@
+pub(crate) mod attrs;
+
+use inflector::Inflector;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Ident, LitStr, Type, Visibility};
+
+pub(crate) fn entity_validate(validations: Vec<TokenStream>, ident: &Ident) -> TokenStream {
+    quote! {
+        impl storm::EntityValidate for #ident {
+            #[allow(unused)]
+            fn entity_validate(&self, error: &mut Option<storm::Error>) {
+                #(#validations)*
+            }
+        }
+    }
+}
+
+pub(crate) fn is_translated(t: &Type) -> bool {
+    match t {
+        Type::Path(p) => p
+            .path
+            .segments
+            .iter()
+            .next_back()
+            .is_some_and(|s| &s.ident == "Translated"),
+        _ => false,
+    }
+}
+
+pub(crate) fn enum_fields_impl(
+    vis: &Visibility,
+    ident: &Ident,
+    fields: Vec<Ident>,
+    enum_ident: &Ident,
+) -> TokenStream {
+    if fields.is_empty() {
+        return quote!();
+    }
+
+    let names = fields
+        .iter()
+        .map(|i| LitStr::new(&i.to_string().to_camel_case(), i.span()));
+
+    quote! {
+        #[derive(Clone, Copy, Eq, serde::Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        #vis enum #enum_ident {
+            #(#fields,)*
+        }
+
+        impl AsRef<str> for #enum_ident {
+            fn as_ref(&self) -> &str {
+                match self {
+                    #(Self::#fields => #names,)*
+                }
+            }
+        }
+
+        impl std::fmt::Debug for #enum_ident {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.as_ref())
+            }
+        }
+
+        impl std::fmt::Display for #enum_ident {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.as_ref())
+            }
+        }
+
+        impl std::hash::Hash for #enum_ident {
+            fn hash<H>(&self, state: &mut H)
+            where
+                H: std::hash::Hasher
+            {
+                (*self as u16).hash(state);
+            }
+        }
+
+        impl std::cmp::PartialEq for #enum_ident {
+            fn eq(&self, other: &Self) -> bool {
+                *self as u16 == *other as u16
+            }
+        }
+
+        impl serde::Serialize for #enum_ident {
+            fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer
+            {
+                self.as_ref().serialize(serializer)
+            }
+        }
+
+        impl storm::Fields for #enum_ident {}
+
+        impl storm::EntityFields for #ident {
+            type Fields = #enum_ident;
+        }
+    }
+}
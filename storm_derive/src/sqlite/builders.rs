@@ -0,0 +1,99 @@
+use crate::StringExt;
+use proc_macro2::Span;
+use syn::LitStr;
+
+#[derive(Clone, Default)]
+pub(super) struct DeleteBuilder {
+    wheres: String,
+}
+
+impl DeleteBuilder {
+    pub fn add_key(&mut self, column: &str, param_index: usize) {
+        add_where(&mut self.wheres, None, column, param_index);
+    }
+
+    pub fn to_sql_lit(&self, table: &str) -> LitStr {
+        LitStr::new(
+            &format!("DELETE FROM {} WHERE {}", table, self.wheres),
+            Span::call_site(),
+        )
+    }
+}
+
+/// Creates a where clauses on the keys for the load sql query.
+#[derive(Clone, Default)]
+pub(super) struct KeysFilterBuilder {
+    count: usize,
+    wheres: String,
+}
+
+impl KeysFilterBuilder {
+    pub fn add_key(&mut self, column: &str) {
+        self.count += 1;
+        add_where(&mut self.wheres, Some("t"), column, self.count);
+    }
+
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn to_sql_lit(&self) -> LitStr {
+        LitStr::new(&self.wheres, Span::call_site())
+    }
+}
+
+#[derive(Clone)]
+pub(super) struct SelectBuilder {
+    alias: &'static str,
+    count: usize,
+    select: String,
+}
+
+impl SelectBuilder {
+    pub fn with_alias(alias: &'static str) -> Self {
+        Self {
+            alias,
+            count: 0,
+            select: String::new(),
+        }
+    }
+
+    pub fn add_field(&mut self, column: &str) -> usize {
+        let index = self.count;
+
+        self.select
+            .add_sep(',')
+            .add_str(self.alias)
+            .add_str(".[")
+            .add_str(column)
+            .add(']');
+
+        self.count += 1;
+
+        index
+    }
+
+    pub fn to_sql_lit(&self, table: &str, where_clause: &str) -> LitStr {
+        let mut sql = format!("SELECT {} FROM {} {}", self.select, table, self.alias);
+
+        if !where_clause.is_empty() {
+            sql.add_str(" WHERE ").add_str(where_clause);
+        }
+
+        LitStr::new(&sql, Span::call_site())
+    }
+}
+
+fn add_where(sql: &mut String, alias: Option<&str>, column: &str, param_index: usize) {
+    sql.add_sep_str(" AND ").add('(');
+
+    if let Some(alias) = alias {
+        sql.add_str(alias).add('.');
+    }
+
+    sql.add('[')
+        .add_str(column)
+        .add_str("]=?")
+        .add_str(&param_index.to_string())
+        .add(')');
+}
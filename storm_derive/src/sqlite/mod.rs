@@ -0,0 +1,496 @@
+mod builders;
+
+use crate::{
+    DeriveInputExt, Errors, FieldExt, RenameAll,
+    sql::{
+        attrs::{FieldAttrs, TypeAttrs, check_required},
+        entity_validate, enum_fields_impl, is_translated,
+    },
+    token_stream_ext::TokenStreamExt,
+};
+use builders::{DeleteBuilder, KeysFilterBuilder, SelectBuilder};
+use darling::{FromDeriveInput, FromField};
+use inflector::Inflector;
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{DeriveInput, Error, Field, Ident, LitInt, LitStr, spanned::Spanned};
+
+pub(crate) fn delete(input: &DeriveInput) -> TokenStream {
+    let ident = &input.ident;
+    let attrs = try_ts!(TypeAttrs::from_derive_input(input).map_err(|e| e.write_errors()));
+    let mut errors = Vec::new();
+    let mut delete = DeleteBuilder::default();
+
+    check_unsupported(&attrs, &mut errors);
+    check_required(&attrs.table, &mut errors);
+
+    let keys = attrs.keys(&mut errors);
+
+    for (index, key) in keys.iter().enumerate() {
+        delete.add_key(key, index + 1);
+    }
+
+    try_ts!(errors.result());
+
+    let sql = delete.to_sql_lit(&attrs.table);
+    let params = keys_params(keys.len());
+    let provider = attrs.provider();
+
+    let no_ctx = if attrs.no_ctx {
+        quote! {}
+    } else {
+        quote! { impl storm::EntityRemove for #ident {} }
+    };
+
+    quote! {
+        #no_ctx
+
+        impl storm::provider::Delete<#ident> for storm::provider::TransactionProvider<'_> {
+            fn delete<'a>(&'a self, k: &'a <#ident as storm::Entity>::Key) -> storm::BoxFuture<'a, storm::Result<()>> {
+                Box::pin(async move {
+                    let provider: &storm_sqlite::SqliteProvider = storm::tri!(self.container().provide(#provider).await);
+                    storm::tri!(storm_sqlite::Execute::execute(provider, #sql, #params).await);
+                    Ok(())
+                })
+            }
+        }
+    }
+}
+
+pub(crate) fn load(input: &DeriveInput) -> TokenStream {
+    let ident = &input.ident;
+    let attrs = try_ts!(TypeAttrs::from_derive_input(input).map_err(|e| e.write_errors()));
+    let rename_all = attrs.rename_all;
+
+    let load_fn = Ident::new(
+        &format!("__load_{}", ident.to_string().to_snake_case()),
+        Span::call_site(),
+    );
+
+    let mut errors = Vec::new();
+    let mut fields = Vec::new();
+    let mut filter = KeysFilterBuilder::default();
+    let mut max_lengths = Vec::new();
+    let mut select = SelectBuilder::with_alias("t");
+
+    check_unsupported(&attrs, &mut errors);
+    check_required(&attrs.table, &mut errors);
+
+    for field in try_ts!(input.fields()) {
+        let field_ident = continue_ts!(field.ident(), errors);
+
+        let attrs = continue_ts!(
+            FieldAttrs::from_field(field).map_err(|e| e.write_errors()),
+            errors
+        );
+
+        attrs.validate_load(&mut errors);
+
+        if is_translated(&field.ty) {
+            errors.push(
+                Error::new(
+                    field.span(),
+                    "Translated fields are not supported by SQLite.",
+                )
+                .to_compile_error(),
+            );
+            continue;
+        }
+
+        let column = continue_ts!(RenameAll::column(rename_all, &attrs.column, field), errors);
+
+        let read = match attrs.load_with.as_ref() {
+            Some(f) => quote!(storm::tri!(#f(row))),
+            None if attrs.skip_load() => quote!(Default::default()),
+            None => read_row(select.add_field(&column)),
+        };
+
+        fields.push(quote!(#field_ident: #read,));
+
+        if attrs.max_length > 0 {
+            let const_field_name = Ident::new(
+                &format!("{field_ident}_MAX_LENGTH").to_screaming_snake_case(),
+                field_ident.span(),
+            );
+            let max_length = LitInt::new(&attrs.max_length.to_string(), field_ident.span());
+
+            max_lengths.push(quote! { pub const #const_field_name: usize = #max_length; });
+        }
+    }
+
+    let keys = attrs.keys(&mut errors);
+    let mut keys_ts = Vec::new();
+
+    for key in &keys {
+        filter.add_key(key);
+        keys_ts.push(read_row(select.add_field(key)));
+    }
+
+    try_ts!(errors.result());
+
+    let keys_ts = match keys_ts.len() == 1 {
+        true => quote!(#(#keys_ts)*),
+        false => quote!((#(#keys_ts,)*)),
+    };
+
+    let sql = select.to_sql_lit(&attrs.table, &attrs.where_clause);
+    let filter_params = keys_params(filter.len());
+    let filter = filter.to_sql_lit();
+    let fields = fields.ts();
+    let provider = attrs.provider();
+    let table_name = LitStr::new(&attrs.table, attrs.table.span());
+
+    let filter_lit = LitStr::new(
+        match attrs.where_clause.is_empty() {
+            true => "{} WHERE {}",
+            false => "{} AND {}",
+        },
+        Span::call_site(),
+    );
+
+    let max_lengths = if max_lengths.is_empty() {
+        quote! {}
+    } else {
+        quote! { impl #ident { #(#max_lengths)* } }
+    };
+
+    quote! {
+        fn #load_fn<'a, C>(provider: &'a storm::provider::ProviderContainer, sql: std::borrow::Cow<'a, str>, params: std::borrow::Cow<'a, [&'a dyn storm_sqlite::ToSql]>, args: storm::provider::LoadArgs) -> storm::BoxFuture<'a, storm::Result<C>>
+        where
+            C: Default + Extend<(<#ident as storm::Entity>::Key, #ident)> + Send + 'static,
+        {
+            Box::pin(async move {
+                let provider: &storm_sqlite::SqliteProvider = storm::tri!(provider.provide(#provider).await);
+
+                const SQL: &str = #sql;
+
+                let load_sql = match sql.is_empty() {
+                    false => format!(#filter_lit, SQL, sql),
+                    true => SQL.to_string(),
+                };
+
+                fn load_row(row: &storm_sqlite::rusqlite::Row<'_>) -> storm::Result<(<#ident as storm::Entity>::Key, #ident)> {
+                    Ok((
+                        #keys_ts,
+                        #ident { #fields }
+                    ))
+                }
+
+                storm_sqlite::QueryRows::query_rows(provider, load_sql, &*params, load_row, args.use_transaction).await
+            })
+        }
+
+        impl<C, FILTER> storm::provider::LoadAll<#ident, FILTER, C> for storm::provider::ProviderContainer
+        where
+            C: Default + Extend<(<#ident as storm::Entity>::Key, #ident)> + Send + 'static,
+            FILTER: storm_sqlite::FilterSql,
+        {
+            fn load_all_with_args<'a>(&'a self, filter: &'a FILTER, args: storm::provider::LoadArgs) -> storm::BoxFuture<'a, storm::Result<C>> {
+                let (sql, params) = storm_sqlite::FilterSql::filter_sql(filter, 0);
+                #load_fn(self, sql, params, args)
+            }
+        }
+
+        impl storm::provider::LoadOne<#ident> for storm::provider::ProviderContainer {
+            fn load_one_with_args<'a>(&'a self, k: &'a <#ident as storm::Entity>::Key, args: storm::provider::LoadArgs) -> storm::BoxFuture<'a, storm::Result<Option<#ident>>> {
+                Box::pin(async move {
+                    let filter = (#filter, #filter_params);
+                    let v: storm::provider::LoadOneInternal<#ident> = storm::tri!(storm::provider::LoadAll::load_all_with_args(self, &filter, args).await);
+                    Ok(v.into_inner())
+                })
+            }
+        }
+
+        impl storm::provider::LoadMany<#ident> for storm::provider::ProviderContainer {}
+
+        impl storm_sqlite::SqliteMeta for #ident {
+            const TABLE: &'static str = #table_name;
+        }
+
+        #max_lengths
+    }
+}
+
+pub(crate) fn save(input: &DeriveInput) -> TokenStream {
+    let ident = &input.ident;
+    let attrs = try_ts!(TypeAttrs::from_derive_input(input).map_err(|e| e.write_errors()));
+    let rename_all = attrs.rename_all;
+
+    let mut entity_validations = Vec::new();
+    let mut errors = Vec::new();
+    let mut save_part = Vec::new();
+    let mut wheres = Vec::new();
+    let is_identity_key = attrs.is_identity_key();
+    let identity_col = attrs.identity.to_lowercase();
+    let mut enum_fields = Vec::new();
+    let enum_fields_ident = Ident::new(&format!("{ident}Fields"), ident.span());
+    let vis = &input.vis;
+
+    check_unsupported(&attrs, &mut errors);
+    check_required(&attrs.table, &mut errors);
+
+    let keys = attrs.keys(&mut errors);
+    let mut identity_found = is_identity_key;
+
+    for field in try_ts!(input.fields()) {
+        let attrs: FieldAttrs = continue_ts!(
+            FieldAttrs::from_field(field).map_err(|e| e.write_errors()),
+            errors
+        );
+
+        attrs.validate_save(&mut errors);
+        check_unsupported_field(&attrs, field, &mut errors);
+
+        if attrs.skip_save() {
+            continue;
+        }
+
+        let column = &continue_ts!(RenameAll::column(rename_all, &attrs.column, field), errors);
+
+        if !identity_col.is_empty() && identity_col == column.to_lowercase() {
+            identity_found = true;
+            continue;
+        }
+
+        // keys are processed at the end.
+        if keys.contains(&column.as_str()) {
+            if attrs.save_with.is_some() {
+                errors.push(
+                    Error::new(attrs.save_with.span(), "Invalid since this field is a key.")
+                        .to_compile_error(),
+                );
+            }
+            continue;
+        }
+
+        let ident = continue_ts!(field.ident(), errors);
+        let field_pascal_ident = Ident::new(&ident.to_string().to_pascal_case(), ident.span());
+
+        if is_translated(&field.ty) {
+            errors.push(
+                Error::new(
+                    field.span(),
+                    "Translated fields are not supported by SQLite.",
+                )
+                .to_compile_error(),
+            );
+            continue;
+        }
+
+        if attrs.max_length > 0 {
+            let expected = LitInt::new(&attrs.max_length.to_string(), Span::call_site());
+
+            entity_validations.push(quote!(
+                storm::macro_check_max_len(storm::Len::len(&self.#ident), #expected, #enum_fields_ident::#field_pascal_ident, error);
+            ));
+        }
+
+        let name = LitStr::new(&format!("[{column}]"), ident.span());
+
+        if attrs.part {
+            save_part.push(
+                quote!(storm_sqlite::SaveEntityPart::save_entity_part(&self.#ident, k, builder);),
+            );
+
+            entity_validations
+                .push(quote!(storm::EntityValidate::entity_validate(&self.#ident, error);));
+        } else {
+            match attrs.save_with.as_ref() {
+                Some(f) => save_part.push(quote!(builder.add_field_owned(#name, #f(k, self));)),
+                None => save_part.push(quote!(builder.add_field_ref(#name, &self.#ident);)),
+            }
+
+            enum_fields.push(field_pascal_ident);
+        }
+    }
+
+    if !attrs.identity.is_empty() && !identity_found {
+        errors.push(
+            Error::new(attrs.identity.span(), "Identity field not found.").to_compile_error(),
+        );
+    }
+
+    let add_key_or_identity = if is_identity_key {
+        quote!(add_key_identity)
+    } else {
+        quote!(add_key_ref)
+    };
+
+    for (index, key) in keys.iter().enumerate() {
+        let name = LitStr::new(&format!("[{key}]"), ident.span());
+
+        let k = match keys.len() > 1 {
+            true => {
+                let n = LitInt::new(&index.to_string(), ident.span());
+                quote! { &k.#n }
+            }
+            false if is_identity_key => quote! { *k },
+            false => quote! { k },
+        };
+
+        wheres.push(quote!(builder.#add_key_or_identity(#name, #k);));
+    }
+
+    try_ts!(errors.result());
+
+    let upsert_trait;
+    let upsert_sig;
+    let entity_part_key;
+
+    if attrs.reload_on_upsert_or_identity() {
+        upsert_trait = quote!(storm::provider::UpsertMut<#ident>);
+        upsert_sig = quote!(fn upsert_mut<'a>(&'a self, k: &'a mut <#ident as storm::Entity>::Key, v: &'a mut #ident) -> storm::BoxFuture<'a, storm::Result<()>>);
+        entity_part_key = quote!(&k.clone());
+    } else {
+        upsert_trait = quote!(storm::provider::Upsert<#ident>);
+        upsert_sig = quote!(fn upsert<'a>(&'a self, k: &'a <#ident as storm::Entity>::Key, v: &'a #ident) -> storm::BoxFuture<'a, storm::Result<()>>);
+        entity_part_key = quote!(k);
+    }
+
+    let reload_entity = if reload_on_upsert(&attrs) {
+        quote! {
+            *v = storm::tri!(storm::provider::LoadOne::<#ident>::load_one_with_args(self.container(), &k, storm::provider::LoadArgs { use_transaction: true }).await.and_then(|v| v.ok_or(storm::Error::EntityNotFound)));
+        }
+    } else {
+        quote!()
+    };
+
+    let builder_invoke = if is_identity_key {
+        quote!(storm::tri!(builder.execute_identity(provider, k).await);)
+    } else {
+        quote!(storm::tri!(builder.execute(provider).await);)
+    };
+
+    let save_part = save_part.ts();
+    let wheres = wheres.ts();
+    let table = LitStr::new(&attrs.table, ident.span());
+    let provider = attrs.provider();
+    let enum_fields = enum_fields_impl(vis, ident, enum_fields, &enum_fields_ident);
+    let entity_validate = entity_validate(entity_validations, ident);
+
+    let no_ctx = if attrs.no_ctx {
+        quote! {}
+    } else if attrs.reload_on_upsert_or_identity() {
+        quote! { impl storm::EntityUpsertMut for #ident {} }
+    } else {
+        quote! { impl storm::EntityUpsert for #ident {} }
+    };
+
+    quote! {
+        impl #upsert_trait for storm::provider::TransactionProvider<'_> {
+            #upsert_sig {
+                Box::pin(async move {
+                    let provider: &storm_sqlite::SqliteProvider = storm::tri!(self.container().provide(#provider).await);
+                    let mut builder = storm_sqlite::UpsertBuilder::new(#table);
+                    let entity_part_key = #entity_part_key;
+
+                    storm_sqlite::SaveEntityPart::save_entity_part(v, entity_part_key, &mut builder);
+
+                    #wheres
+                    #builder_invoke
+                    #reload_entity
+
+                    Ok(())
+                })
+            }
+        }
+
+        #enum_fields
+        #entity_validate
+
+        impl storm_sqlite::SaveEntityPart for #ident {
+            fn save_entity_part<'a>(&'a self, k: &'a Self::Key, builder: &mut storm_sqlite::UpsertBuilder<'a>) {
+                #save_part
+            }
+        }
+
+        #no_ctx
+    }
+}
+
+/// Attributes parsed for the mssql derives that have no SQLite counterpart.
+fn check_unsupported(attrs: &TypeAttrs, errors: &mut Vec<TokenStream>) {
+    if attrs.diff {
+        errors.push(
+            Error::new(Span::call_site(), "`diff` is not supported by SQLite.").to_compile_error(),
+        );
+    }
+
+    if !attrs.translate_table.is_empty() {
+        errors.push(
+            Error::new(
+                attrs.translate_table.span(),
+                "`translate_table` is not supported by SQLite.",
+            )
+            .to_compile_error(),
+        );
+    }
+
+    if !attrs.translate_keys.is_empty() {
+        errors.push(
+            Error::new(
+                attrs.translate_keys.span(),
+                "`translate_keys` is not supported by SQLite.",
+            )
+            .to_compile_error(),
+        );
+    }
+
+    if attrs.change_tracking {
+        errors.push(
+            Error::new(
+                Span::call_site(),
+                "`change_tracking` is not supported by SQLite.",
+            )
+            .to_compile_error(),
+        );
+    }
+
+    if attrs.partition.is_some() {
+        errors.push(
+            Error::new(
+                attrs.partition.span(),
+                "`partition` is not supported by SQLite.",
+            )
+            .to_compile_error(),
+        );
+    }
+}
+
+fn check_unsupported_field(attrs: &FieldAttrs, field: &Field, errors: &mut Vec<TokenStream>) {
+    let unsupported = [
+        (
+            attrs.concurrency_token,
+            "`concurrency_token` is not supported by SQLite.",
+        ),
+        (attrs.generated, "`generated` is not supported by SQLite."),
+        (attrs.skip_diff, "`skip_diff` is not supported by SQLite."),
+    ];
+
+    for (_, msg) in unsupported.iter().filter(|(set, _)| *set) {
+        errors.push(Error::new(field.span(), msg).to_compile_error());
+    }
+}
+
+fn keys_params(len: usize) -> TokenStream {
+    if len == 1 {
+        return quote!(&[k as _][..]);
+    }
+
+    let params = (0..len).map(|i| {
+        let i = LitInt::new(&i.to_string(), Span::call_site());
+        quote!(&k.#i as _)
+    });
+
+    quote!(&[#(#params,)*][..])
+}
+
+/// SQLite has no `OUTPUT INSERTED`, the entity is read back when an identity column is not the key.
+fn reload_on_upsert(attrs: &TypeAttrs) -> bool {
+    attrs.reload_on_upsert || (!attrs.identity.is_empty() && !attrs.is_identity_key())
+}
+
+fn read_row(column_index: usize) -> TokenStream {
+    let l = LitInt::new(&column_index.to_string(), Span::call_site());
+    quote!(storm::tri!(storm_sqlite::_macro_load_field(row, #l)))
+}
@@ -0,0 +1,164 @@
+use crate::{FilterSql, MssqlMeta, MssqlProvider, QueryRows, ToSql};
+use parking_lot::Mutex;
+use std::{any::TypeId, borrow::Cow};
+use storm::{
+    BoxFuture, Ctx, EntityAccessor, Error, ProviderContainer, Result, provider::LoadAll,
+    rustc_hash::FxHashMap,
+};
+use tiberius::Row;
+
+storm::extobj::extobj!(
+    impl storm::CtxExt {
+        CHANGE_VERSIONS: Mutex<FxHashMap<TypeId, i64>>,
+    },
+    crate_path = storm::extobj
+);
+
+/// An entity loaded from a table with SQL Server change tracking enabled.
+///
+/// Implemented by the `MssqlLoad` derive with `#[storm(change_tracking)]`, see [sync_tbl_of].
+pub trait ChangeTracking: EntityAccessor + MssqlMeta {
+    /// The key columns of the table.
+    const KEYS: &'static [&'static str];
+
+    /// The name of the provider in the ProviderContainer.
+    const PROVIDER: &'static str;
+
+    fn read_key(row: Row) -> Result<Self::Key>;
+}
+
+/// Filters the rows of a table changed since a change tracking version.
+pub struct ChangedSince<'a> {
+    pub keys: &'a [&'a str],
+    pub table: &'a str,
+    pub version: i64,
+}
+
+impl FilterSql for ChangedSince<'_> {
+    fn filter_sql(&self, param_index: usize) -> (Cow<'_, str>, Cow<'_, [&'_ dyn ToSql]>) {
+        let join = self
+            .keys
+            .iter()
+            .map(|k| format!("ct.[{k}] = t.[{k}]"))
+            .collect::<Vec<_>>()
+            .join(" AND ");
+
+        (
+            Cow::Owned(format!(
+                "EXISTS (SELECT 1 FROM CHANGETABLE(CHANGES {}, @p{}) ct WHERE {join})",
+                self.table,
+                param_index + 1
+            )),
+            Cow::Owned(vec![&self.version as _]),
+        )
+    }
+}
+
+/// Loads the rows changed since the last sync of a table using SQL Server change tracking and
+/// applies them to the ctx with [Ctx::apply_tbl_changes]. Returns true if something changed.
+///
+/// The table is fully refreshed with [Ctx::refresh_tbl_of] on the first sync and when the
+/// database no longer retains the changes since the last sync. A table that is not loaded is
+/// left untouched.
+pub fn sync_tbl_of<E>(ctx: &mut Ctx) -> BoxFuture<'_, Result<bool>>
+where
+    E: ChangeTracking + PartialEq,
+    ProviderContainer: LoadAll<E, (), FxHashMap<E::Key, E>>
+        + for<'a> LoadAll<E, ChangedSince<'a>, FxHashMap<E::Key, E>>,
+{
+    Box::pin(async move {
+        if ctx.tbl_of_opt::<E>().is_none() {
+            return Ok(false);
+        }
+
+        let provider: &MssqlProvider = ctx.provider().provide(E::PROVIDER).await?;
+        let (current, min_valid) = versions(provider, E::TABLE).await?;
+        let last = change_versions(ctx).lock().get(&TypeId::of::<E>()).copied();
+
+        let changed = match last {
+            Some(last) if last >= min_valid => {
+                let changes = load_changes::<E>(ctx.provider(), provider, last).await?;
+                ctx.apply_tbl_changes::<E, _>(changes)
+            }
+            _ => ctx.refresh_tbl_of::<E>().await?,
+        };
+
+        change_versions(ctx)
+            .lock()
+            .insert(TypeId::of::<E>(), current);
+
+        Ok(changed)
+    })
+}
+
+fn change_versions(ctx: &Ctx) -> &Mutex<FxHashMap<TypeId, i64>> {
+    ctx.ctx_ext_obj().get(*CHANGE_VERSIONS)
+}
+
+/// Loads the keys changed since the version, with the current row or `None` if deleted.
+async fn load_changes<E>(
+    container: &ProviderContainer,
+    provider: &MssqlProvider,
+    version: i64,
+) -> Result<Vec<(E::Key, Option<E>)>>
+where
+    E: ChangeTracking,
+    ProviderContainer: for<'a> LoadAll<E, ChangedSince<'a>, FxHashMap<E::Key, E>>,
+{
+    let columns = E::KEYS
+        .iter()
+        .map(|k| format!("ct.[{k}]"))
+        .collect::<Vec<_>>()
+        .join(",");
+
+    let sql = format!(
+        "SELECT {columns} FROM CHANGETABLE(CHANGES {}, @p1) ct",
+        E::TABLE
+    );
+
+    let keys: Vec<E::Key> = provider
+        .query_rows(sql, &[&version], E::read_key, false)
+        .await?;
+
+    if keys.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let filter = ChangedSince {
+        keys: E::KEYS,
+        table: E::TABLE,
+        version,
+    };
+
+    let mut rows: FxHashMap<E::Key, E> = container.load_all(&filter).await?;
+
+    Ok(keys
+        .into_iter()
+        .map(|k| {
+            let row = rows.remove(&k);
+            (k, row)
+        })
+        .collect())
+}
+
+/// Returns the current change tracking version of the database and the minimum valid
+/// version of the table.
+async fn versions(provider: &MssqlProvider, table: &str) -> Result<(i64, i64)> {
+    const SQL: &str = "SELECT CHANGE_TRACKING_CURRENT_VERSION(), CHANGE_TRACKING_MIN_VALID_VERSION(OBJECT_ID(@p1))";
+
+    let rows: Vec<(Option<i64>, Option<i64>)> = provider
+        .query_rows(
+            SQL.to_string(),
+            &[&table],
+            |row| Ok((row.get(0), row.get(1))),
+            false,
+        )
+        .await?;
+
+    match rows.first() {
+        Some((Some(current), Some(min_valid))) => Ok((*current, *min_valid)),
+        _ => Err(Error::String(format!(
+            "Change tracking is not enabled on table `{table}`."
+        ))),
+    }
+}
@@ -0,0 +1,144 @@
+use crate::{Client, ClientFactory, error::map_error};
+use parking_lot::Mutex;
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+use storm::{Error, Result};
+use tokio::sync::{Semaphore, SemaphorePermit};
+use tracing::warn;
+
+/// The options of the pool of clients used by the [MssqlProvider](crate::MssqlProvider)
+/// for the non-transactional operations.
+#[derive(Clone, Debug)]
+pub struct PoolOptions {
+    /// The number of idle clients kept open, even after the idle timeout.
+    pub min_size: usize,
+
+    /// The maximum number of clients checked out at the same time.
+    pub max_size: usize,
+
+    /// The duration after which an idle client is closed.
+    pub idle_timeout: Duration,
+}
+
+impl Default for PoolOptions {
+    fn default() -> Self {
+        Self {
+            min_size: 1,
+            max_size: 10,
+            idle_timeout: Duration::from_secs(300),
+        }
+    }
+}
+
+pub(crate) struct ClientPool {
+    idle: Mutex<VecDeque<IdleClient>>,
+    lock_timeout: Mutex<Option<Duration>>,
+    options: PoolOptions,
+    semaphore: Semaphore,
+}
+
+impl ClientPool {
+    pub fn new(options: PoolOptions, lock_timeout: Option<Duration>) -> Self {
+        Self {
+            idle: Default::default(),
+            lock_timeout: Mutex::new(lock_timeout),
+            semaphore: Semaphore::new(options.max_size.max(1)),
+            options,
+        }
+    }
+
+    /// Gets an idle client that is still alive or creates a new one.
+    pub async fn checkout<'a>(&'a self, factory: &dyn ClientFactory) -> Result<PooledClient<'a>> {
+        let permit = self.semaphore.acquire().await.map_err(Error::std)?;
+        let lock_timeout = *self.lock_timeout.lock();
+
+        while let Some(mut idle) = self.pop_idle() {
+            if let Err(e) = health_check(&mut idle.client).await {
+                warn!(error = %e, "pooled client dropped");
+                continue;
+            }
+
+            if idle.lock_timeout != lock_timeout {
+                crate::mssql_provider::set_client_lock_timeout(&mut idle.client, lock_timeout)
+                    .await?;
+            }
+
+            return Ok(PooledClient {
+                client: idle.client,
+                lock_timeout,
+                pool: self,
+                _permit: permit,
+            });
+        }
+
+        let mut client = factory.create_client().await?;
+
+        crate::mssql_provider::set_client_lock_timeout(&mut client, lock_timeout).await?;
+
+        Ok(PooledClient {
+            client,
+            lock_timeout,
+            pool: self,
+            _permit: permit,
+        })
+    }
+
+    fn pop_idle(&self) -> Option<IdleClient> {
+        let mut idle = self.idle.lock();
+
+        // the oldest clients are at the front.
+        while idle.len() > self.options.min_size
+            && idle
+                .front()
+                .is_some_and(|c| c.since.elapsed() > self.options.idle_timeout)
+        {
+            idle.pop_front();
+        }
+
+        idle.pop_back()
+    }
+
+    pub fn set_lock_timeout(&self, timeout: Option<Duration>) {
+        *self.lock_timeout.lock() = timeout;
+    }
+}
+
+/// A client checked out of the pool.
+///
+/// The client is returned to the pool only when [release](Self::release) is called,
+/// a client dropped after a failure is closed.
+pub(crate) struct PooledClient<'a> {
+    pub client: Client,
+    lock_timeout: Option<Duration>,
+    pool: &'a ClientPool,
+    _permit: SemaphorePermit<'a>,
+}
+
+impl PooledClient<'_> {
+    pub fn release(self) {
+        self.pool.idle.lock().push_back(IdleClient {
+            client: self.client,
+            lock_timeout: self.lock_timeout,
+            since: Instant::now(),
+        });
+    }
+}
+
+struct IdleClient {
+    client: Client,
+    lock_timeout: Option<Duration>,
+    since: Instant,
+}
+
+async fn health_check(client: &mut Client) -> Result<()> {
+    client
+        .simple_query("SELECT 1")
+        .await
+        .map_err(map_error)?
+        .into_results()
+        .await
+        .map_err(map_error)?;
+    Ok(())
+}
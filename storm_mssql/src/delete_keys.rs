@@ -0,0 +1,19 @@
+use crate::{Execute, FilterSql, KeysFilter, MAX_PARAMS, Result, ToSql};
+
+/// Deletes the rows of a table having their key in the list, sending the keys in chunks
+/// to respect the parameter limit of SQL Server.
+pub async fn delete_keys<P, K>(provider: &P, table: &str, column: &str, keys: &[K]) -> Result<()>
+where
+    P: Execute,
+    K: ToSql,
+{
+    for keys in keys.chunks(MAX_PARAMS) {
+        let filter = KeysFilter(column, keys);
+        let (filter, params) = filter.filter_sql(0);
+        let sql = format!("DELETE FROM {table} WHERE {filter}");
+
+        provider.execute(sql, &params).await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,153 @@
+use std::io::ErrorKind;
+use storm::Error;
+
+/// Maps the errors of SQL Server to the structured variants of [Error], so they can be matched
+/// without parsing the messages. The other errors are kept as [Error::Mssql].
+pub(crate) fn map_error(e: tiberius::error::Error) -> Error {
+    let mapped = match &e {
+        tiberius::error::Error::Io {
+            kind: ErrorKind::TimedOut,
+            ..
+        } => Some(Error::Timeout),
+        tiberius::error::Error::Server(token) => map_server_error(token.code(), token.message()),
+        _ => None,
+    };
+
+    mapped.unwrap_or(Error::Mssql(e))
+}
+
+fn map_server_error(code: u32, message: &str) -> Option<Error> {
+    // the names are read from the english messages and left empty for the other languages.
+    let quoted =
+        |marker: &str| -> Box<str> { quoted_after(message, marker).unwrap_or_default().into() };
+
+    match code {
+        // Violation of PRIMARY KEY constraint 'PK'. Cannot insert duplicate key in object 'T'.
+        2627 => Some(Error::UniqueViolation {
+            constraint: quoted("constraint "),
+            table: quoted("object "),
+        }),
+
+        // Cannot insert duplicate key row in object 'T' with unique index 'IX'.
+        2601 => Some(Error::UniqueViolation {
+            constraint: quoted("index "),
+            table: quoted("object "),
+        }),
+
+        // The INSERT statement conflicted with the FOREIGN KEY constraint "FK". The conflict
+        // occurred in database "D", table "T", column 'C'. The CHECK constraints share the code,
+        // the keywords are not translated.
+        547 if message.contains("FOREIGN KEY")
+            || message.contains("REFERENCE")
+            || !message.contains("CHECK") =>
+        {
+            Some(Error::ForeignKeyViolation {
+                constraint: quoted("constraint "),
+                table: quoted("table "),
+            })
+        }
+
+        1205 => Some(Error::Deadlock),
+        1222 => Some(Error::Timeout),
+
+        // String or binary data would be truncated in table 'T', column 'C'.
+        2628 | 8152 => Some(Error::Truncation {
+            column: quoted("column "),
+            table: quoted("table "),
+        }),
+
+        _ => None,
+    }
+}
+
+/// The text between the quotes following `marker`.
+fn quoted_after<'a>(message: &'a str, marker: &str) -> Option<&'a str> {
+    let (_, s) = message.split_once(marker)?;
+    let mut chars = s.chars();
+    let quote = chars.next().filter(|c| *c == '\'' || *c == '"')?;
+
+    chars.as_str().split_once(quote).map(|t| t.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unique_violation() {
+        let e = map_server_error(
+            2627,
+            "Violation of PRIMARY KEY constraint 'PK_T'. Cannot insert duplicate key in object 'dbo.T'. The duplicate key value is (1).",
+        );
+
+        assert!(matches!(
+            e,
+            Some(Error::UniqueViolation { constraint, table }) if &*constraint == "PK_T" && &*table == "dbo.T"
+        ));
+
+        let e = map_server_error(
+            2601,
+            "Cannot insert duplicate key row in object 'dbo.T' with unique index 'IX_T_Name'. The duplicate key value is (a).",
+        );
+
+        assert!(matches!(
+            e,
+            Some(Error::UniqueViolation { constraint, table }) if &*constraint == "IX_T_Name" && &*table == "dbo.T"
+        ));
+
+        assert!(matches!(
+            map_server_error(2627, "Verletzung der PRIMARY KEY-Einschr\u{e4}nkung \u{bb}PK_T\u{ab}."),
+            Some(Error::UniqueViolation { constraint, table }) if constraint.is_empty() && table.is_empty()
+        ));
+    }
+
+    #[test]
+    fn foreign_key_violation() {
+        let e = map_server_error(
+            547,
+            "The DELETE statement conflicted with the REFERENCE constraint \"FK_C_T\". The conflict occurred in database \"db\", table \"dbo.C\", column 'TId'.",
+        );
+
+        assert!(matches!(
+            e,
+            Some(Error::ForeignKeyViolation { constraint, table }) if &*constraint == "FK_C_T" && &*table == "dbo.C"
+        ));
+
+        let e = map_server_error(
+            547,
+            "The INSERT statement conflicted with the CHECK constraint \"CK_T\". The conflict occurred in database \"db\", table \"dbo.T\", column 'Age'.",
+        );
+
+        assert!(e.is_none());
+
+        assert!(matches!(
+            map_server_error(547, "Die INSERT-Anweisung steht in Konflikt mit der FOREIGN KEY-Einschr\u{e4}nkung \u{bb}FK_C_T\u{ab}."),
+            Some(Error::ForeignKeyViolation { constraint, .. }) if constraint.is_empty()
+        ));
+    }
+
+    #[test]
+    fn truncation() {
+        let e = map_server_error(
+            2628,
+            "String or binary data would be truncated in table 'db.dbo.T', column 'Name'. Truncated value: 'abc'.",
+        );
+
+        assert!(matches!(
+            e,
+            Some(Error::Truncation { column, table }) if &*column == "Name" && &*table == "db.dbo.T"
+        ));
+
+        assert!(matches!(
+            map_server_error(8152, "String or binary data would be truncated."),
+            Some(Error::Truncation { .. })
+        ));
+    }
+
+    #[test]
+    fn deadlock_and_timeout() {
+        assert!(matches!(map_server_error(1205, ""), Some(Error::Deadlock)));
+        assert!(matches!(map_server_error(1222, ""), Some(Error::Timeout)));
+        assert!(map_server_error(50000, "").is_none());
+    }
+}
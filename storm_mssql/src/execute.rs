@@ -1,6 +1,7 @@
 use crate::ToSql;
 use std::{borrow::Cow, fmt::Debug};
 use storm::{BoxFuture, Result};
+use tiberius::Row;
 
 pub trait Execute {
     fn execute_with_args<'a, S>(
@@ -23,6 +24,23 @@ pub trait Execute {
     {
         self.execute_with_args(statement, params, ExecuteArgs::default())
     }
+
+    /// Executes a statement returning rows, like an `OUTPUT` clause, and maps them.
+    ///
+    /// Unlike [QueryRows::query_rows](crate::QueryRows::query_rows), the statement is never
+    /// retried since it writes.
+    fn execute_rows<'a, S, M, R, C>(
+        &'a self,
+        statement: S,
+        params: &'a [&'a dyn ToSql],
+        mapper: M,
+        args: ExecuteArgs,
+    ) -> BoxFuture<'a, Result<C>>
+    where
+        C: Default + Extend<R> + Send,
+        M: FnMut(Row) -> Result<R> + Send + 'a,
+        R: Send,
+        S: Debug + Into<Cow<'a, str>> + Send + 'a;
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -0,0 +1,365 @@
+use crate::{FilterSql, MAX_PARAMS, ToSql};
+use std::{borrow::Cow, fmt::Write, marker::PhantomData, ops::Not};
+use storm::{Error, Result};
+
+/// A column of the table of an entity, used to build a typed [Filter].
+///
+/// The column is referenced using the `t` alias of the load query.
+///
+/// ```
+/// use storm_mssql::Column;
+///
+/// const NAME: Column<String> = Column::new("Name");
+/// const AGE: Column<i32> = Column::new("Age");
+///
+/// let filter = NAME.like("a%").and(AGE.between(18, 65));
+/// ```
+///
+/// The `MssqlLoad` derive generates a column for each loaded field in the `COLUMNS` const
+/// of the entity, e.g. `User::COLUMNS.name`, and for the key of a single key entity.
+pub struct Column<T> {
+    max_length: usize,
+    name: &'static str,
+    _t: PhantomData<fn() -> T>,
+}
+
+impl<T> Column<T> {
+    /// Creates a column from its sql name, without the brackets.
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            max_length: 0,
+            name,
+            _t: PhantomData,
+        }
+    }
+
+    pub const fn with_max_length(mut self, max_length: usize) -> Self {
+        self.max_length = max_length;
+        self
+    }
+
+    /// The maximum length of the column, 0 when unlimited.
+    pub const fn max_length(&self) -> usize {
+        self.max_length
+    }
+
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn is_null(self) -> Filter {
+        Filter(Expr::Null {
+            column: self.name,
+            not: false,
+        })
+    }
+
+    pub fn is_not_null(self) -> Filter {
+        Filter(Expr::Null {
+            column: self.name,
+            not: true,
+        })
+    }
+}
+
+impl<T> Column<T>
+where
+    T: ToSql + 'static,
+{
+    fn compare(self, op: &'static str, value: impl Into<T>) -> Filter {
+        Filter(Expr::Compare {
+            column: self.name,
+            op,
+            value: Box::new(value.into()),
+        })
+    }
+
+    pub fn eq(self, value: impl Into<T>) -> Filter {
+        self.compare("=", value)
+    }
+
+    pub fn ne(self, value: impl Into<T>) -> Filter {
+        self.compare("<>", value)
+    }
+
+    pub fn lt(self, value: impl Into<T>) -> Filter {
+        self.compare("<", value)
+    }
+
+    pub fn le(self, value: impl Into<T>) -> Filter {
+        self.compare("<=", value)
+    }
+
+    pub fn gt(self, value: impl Into<T>) -> Filter {
+        self.compare(">", value)
+    }
+
+    pub fn ge(self, value: impl Into<T>) -> Filter {
+        self.compare(">=", value)
+    }
+
+    /// The value of the column is between low and high, inclusively.
+    pub fn between(self, low: impl Into<T>, high: impl Into<T>) -> Filter {
+        Filter(Expr::Between {
+            column: self.name,
+            low: Box::new(low.into()),
+            high: Box::new(high.into()),
+        })
+    }
+
+    /// The value of the column is one of the values. An empty list matches no rows.
+    ///
+    /// Each value is a parameter of the statement, a list of more than [MAX_PARAMS] values is
+    /// rejected and must be loaded in chunks.
+    pub fn in_list<I>(self, values: I) -> Result<Filter>
+    where
+        I: IntoIterator,
+        I::Item: Into<T>,
+    {
+        let values = values
+            .into_iter()
+            .map(|v| Box::new(v.into()) as Box<dyn ToSql>)
+            .collect::<Vec<_>>();
+
+        if values.len() > MAX_PARAMS {
+            return Err(Error::String(format!(
+                "the IN list of the column `{}` has {} values, the maximum is {MAX_PARAMS}",
+                self.name,
+                values.len()
+            )));
+        }
+
+        Ok(Filter(Expr::In {
+            column: self.name,
+            values,
+        }))
+    }
+}
+
+impl<T> Column<T>
+where
+    T: TextColumn,
+{
+    /// The value of the column matches the sql LIKE pattern.
+    pub fn like(self, pattern: impl Into<String>) -> Filter {
+        Filter(Expr::Compare {
+            column: self.name,
+            op: " LIKE ",
+            value: Box::new(pattern.into()),
+        })
+    }
+}
+
+impl<T> Clone for Column<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Column<T> {}
+
+/// The column types supporting the [like](Column::like) filter.
+pub trait TextColumn {}
+
+impl TextColumn for Box<str> {}
+impl TextColumn for String {}
+impl<T: TextColumn> TextColumn for Option<T> {}
+
+/// A composable filter built from the [Column] comparisons, the parameters are numbered
+/// automatically and the values are never inlined in the sql.
+pub struct Filter(Expr);
+
+impl Filter {
+    pub fn and(self, other: Filter) -> Filter {
+        Filter(Expr::And(self.into_list(Self::is_and, other)))
+    }
+
+    pub fn or(self, other: Filter) -> Filter {
+        Filter(Expr::Or(self.into_list(Self::is_or, other)))
+    }
+
+    fn into_list(self, same: fn(&Expr) -> bool, other: Filter) -> Vec<Expr> {
+        let mut list = self.0.flatten(same);
+        list.extend(other.0.flatten(same));
+        list
+    }
+
+    fn is_and(expr: &Expr) -> bool {
+        matches!(expr, Expr::And(_))
+    }
+
+    fn is_or(expr: &Expr) -> bool {
+        matches!(expr, Expr::Or(_))
+    }
+}
+
+impl FilterSql for Filter {
+    fn filter_sql(&self, param_index: usize) -> (Cow<'_, str>, Cow<'_, [&'_ dyn ToSql]>) {
+        let mut sql = String::new();
+        let mut params = Vec::new();
+
+        self.0.write(&mut sql, &mut params, param_index);
+
+        (Cow::Owned(sql), Cow::Owned(params))
+    }
+}
+
+impl Not for Filter {
+    type Output = Filter;
+
+    fn not(self) -> Filter {
+        Filter(Expr::Not(Box::new(self.0)))
+    }
+}
+
+enum Expr {
+    And(Vec<Expr>),
+    Between {
+        column: &'static str,
+        low: Box<dyn ToSql>,
+        high: Box<dyn ToSql>,
+    },
+    Compare {
+        column: &'static str,
+        op: &'static str,
+        value: Box<dyn ToSql>,
+    },
+    In {
+        column: &'static str,
+        values: Vec<Box<dyn ToSql>>,
+    },
+    Not(Box<Expr>),
+    Null {
+        column: &'static str,
+        not: bool,
+    },
+    Or(Vec<Expr>),
+}
+
+impl Expr {
+    /// Returns the children of an expression of the same kind, for `a AND (b AND c)`.
+    fn flatten(self, same: fn(&Expr) -> bool) -> Vec<Expr> {
+        if !same(&self) {
+            return vec![self];
+        }
+
+        match self {
+            Self::And(list) | Self::Or(list) => list,
+            expr => vec![expr],
+        }
+    }
+
+    fn write<'a>(&'a self, sql: &mut String, params: &mut Vec<&'a dyn ToSql>, index: usize) {
+        fn param<'a>(
+            sql: &mut String,
+            params: &mut Vec<&'a dyn ToSql>,
+            index: usize,
+            value: &'a dyn ToSql,
+        ) {
+            params.push(value);
+            let _ = write!(sql, "@p{}", index + params.len());
+        }
+
+        match self {
+            Self::And(list) => write_list(list, " AND ", sql, params, index),
+            Self::Between { column, low, high } => {
+                let _ = write!(sql, "(t.[{column}] BETWEEN ");
+                param(sql, params, index, &**low);
+                sql.push_str(" AND ");
+                param(sql, params, index, &**high);
+                sql.push(')');
+            }
+            Self::Compare { column, op, value } => {
+                let _ = write!(sql, "(t.[{column}]{op}");
+                param(sql, params, index, &**value);
+                sql.push(')');
+            }
+            Self::In { values, .. } if values.is_empty() => sql.push_str("(1=0)"),
+            Self::In { column, values } => {
+                let _ = write!(sql, "(t.[{column}] IN (");
+
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        sql.push(',');
+                    }
+
+                    param(sql, params, index, &**value);
+                }
+
+                sql.push_str("))");
+            }
+            Self::Not(expr) => {
+                sql.push_str("(NOT ");
+                expr.write(sql, params, index);
+                sql.push(')');
+            }
+            Self::Null { column, not } => {
+                let not = if *not { "NOT " } else { "" };
+                let _ = write!(sql, "(t.[{column}] IS {not}NULL)");
+            }
+            Self::Or(list) => write_list(list, " OR ", sql, params, index),
+        }
+    }
+}
+
+fn write_list<'a>(
+    list: &'a [Expr],
+    sep: &str,
+    sql: &mut String,
+    params: &mut Vec<&'a dyn ToSql>,
+    index: usize,
+) {
+    sql.push('(');
+
+    for (i, expr) in list.iter().enumerate() {
+        if i > 0 {
+            sql.push_str(sep);
+        }
+
+        expr.write(sql, params, index);
+    }
+
+    sql.push(')');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AGE: Column<i32> = Column::new("Age");
+    const NAME: Column<Option<String>> = Column::new("Name");
+
+    #[test]
+    fn numbered_params() -> Result<()> {
+        let filter = NAME
+            .like("a%")
+            .and(AGE.between(1, 5))
+            .and(!AGE.in_list([2, 3])?.or(NAME.is_null()));
+
+        let (sql, params) = filter.filter_sql(2);
+
+        assert_eq!(
+            sql,
+            "((t.[Name] LIKE @p3) AND (t.[Age] BETWEEN @p4 AND @p5) AND (NOT ((t.[Age] IN (@p6,@p7)) OR (t.[Name] IS NULL))))"
+        );
+
+        assert_eq!(params.len(), 5);
+        Ok(())
+    }
+
+    #[test]
+    fn empty_in_list() -> Result<()> {
+        let filter = AGE.in_list(Vec::<i32>::new())?;
+        let (sql, params) = filter.filter_sql(0);
+
+        assert_eq!(sql, "(1=0)");
+        assert!(params.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_above_max_params() {
+        assert!(AGE.in_list(0..=MAX_PARAMS as i32).is_err());
+        assert!(AGE.in_list(1..=MAX_PARAMS as i32).is_ok());
+    }
+}
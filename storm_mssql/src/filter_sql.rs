@@ -59,3 +59,21 @@ where
         )
     }
 }
+
+/// Filters the rows of a partition, see [storm::PartitionedEntity].
+pub struct PartitionFilter<P> {
+    pub column: &'static str,
+    pub value: P,
+}
+
+impl<P> FilterSql for PartitionFilter<P>
+where
+    P: ToSql,
+{
+    fn filter_sql(&self, param_index: usize) -> (Cow<'_, str>, Cow<'_, [&'_ dyn ToSql]>) {
+        (
+            Cow::Owned(format!("{} = @p{}", self.column, param_index + 1)),
+            Cow::Owned(vec![&self.value as _]),
+        )
+    }
+}
@@ -1,16 +1,27 @@
+mod change_tracking;
 mod client_factory;
+mod client_pool;
+mod delete_keys;
 mod entity_diff;
+mod error;
 mod execute;
 mod field_diff;
+mod filter;
 mod filter_sql;
 mod from_sql;
+mod load_page;
+mod load_stream;
+mod merge_builder;
 #[doc(hidden)]
 pub mod metrics_helper;
 mod mssql_factory;
 mod mssql_meta;
 mod mssql_provider;
+mod mssql_provider_options;
 mod parameter;
 mod query_rows;
+mod recovery_log;
+mod row_version;
 mod save_entity_part;
 mod to_sql;
 mod transaction_scoped;
@@ -18,17 +29,27 @@ mod upsert_builder;
 
 use std::pin::Pin;
 
+pub use change_tracking::{ChangeTracking, ChangedSince, sync_tbl_of};
 pub use client_factory::ClientFactory;
+pub use client_pool::PoolOptions;
+pub use delete_keys::delete_keys;
 pub use entity_diff::*;
 pub use execute::*;
 pub use field_diff::*;
+pub use filter::{Column, Filter, TextColumn};
 pub use filter_sql::*;
 pub use from_sql::{_macro_load_field, FromSql};
-pub use mssql_factory::MssqlFactory;
+pub use load_page::{_MacroPage, LoadPage, OrderBy, Page, PageToken};
+pub use load_stream::_macro_load_stream;
+pub use merge_builder::MergeBuilder;
+pub use mssql_factory::{MssqlFactory, MssqlFactoryWithOptions};
 pub use mssql_meta::MssqlMeta;
 pub use mssql_provider::{MssqlProvider, MssqlTransactionGuard};
+pub use mssql_provider_options::{DEFAULT_LOCK_TIMEOUT, MssqlProviderOptions};
 pub use parameter::{Parameter, into_column_data_static};
 pub use query_rows::QueryRows;
+pub use recovery_log::MssqlRecoveryLog;
+pub use row_version::RowVersion;
 pub use save_entity_part::SaveEntityPart;
 pub use serde_json;
 use std::future::Future;
@@ -52,6 +73,9 @@ pub fn create_provider_container_from_env(env_var: &str, name: &str) -> Result<P
 
 type MaxLength = usize;
 
+/// The maximum number of parameters sent in a single statement, SQL Server allows 2100.
+pub const MAX_PARAMS: usize = 2000;
+
 #[doc(hidden)]
 #[allow(clippy::expect_used)]
 #[allow(clippy::panic)]
@@ -0,0 +1,274 @@
+use crate::{Column, FilterSql, ToSql};
+use std::{borrow::Cow, fmt::Write};
+use storm::{BoxFuture, Entity, Error, Result, provider::LoadArgs};
+
+/// Loads a page of rows without caching the table in the ctx.
+///
+/// Without [OrderBy], the rows are ordered by the key and the next page starts after the last
+/// key of the page. Otherwise, the rows are ordered by the columns, then by the keys, and the
+/// next page skips the rows already read. A token returned for the other ordering is an error.
+///
+/// ```ignore
+/// let mut after = None;
+///
+/// loop {
+///     let page = LoadPage::<User, _>::load_page(provider, &filter, &[], after.as_ref(), 50).await?;
+///     export(page.rows);
+///
+///     match page.next {
+///         Some(next) => after = Some(next),
+///         None => break,
+///     }
+/// }
+/// ```
+pub trait LoadPage<E: Entity, FILTER: Send + Sync>: Send + Sync {
+    fn load_page_with_args<'a>(
+        &'a self,
+        filter: &'a FILTER,
+        order_by: &'a [OrderBy],
+        after: Option<&'a PageToken<E::Key>>,
+        limit: usize,
+        args: LoadArgs,
+    ) -> BoxFuture<'a, Result<Page<E::Key, E>>>;
+
+    fn load_page<'a>(
+        &'a self,
+        filter: &'a FILTER,
+        order_by: &'a [OrderBy],
+        after: Option<&'a PageToken<E::Key>>,
+        limit: usize,
+    ) -> BoxFuture<'a, Result<Page<E::Key, E>>> {
+        self.load_page_with_args(filter, order_by, after, limit, LoadArgs::default())
+    }
+}
+
+/// The order of the rows of a page, created by [Column::asc] or [Column::desc].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct OrderBy {
+    column: &'static str,
+    desc: bool,
+}
+
+impl<T> Column<T> {
+    pub fn asc(self) -> OrderBy {
+        OrderBy {
+            column: self.name(),
+            desc: false,
+        }
+    }
+
+    pub fn desc(self) -> OrderBy {
+        OrderBy {
+            column: self.name(),
+            desc: true,
+        }
+    }
+}
+
+/// The rows of a page and the token to load the next one.
+#[derive(Debug)]
+pub struct Page<K, E> {
+    pub rows: Vec<(K, E)>,
+
+    /// None when this is the last page.
+    pub next: Option<PageToken<K>>,
+}
+
+/// The position of a page, returned by the previous page.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PageToken<K> {
+    /// The rows after this key, when the rows are ordered by a single key.
+    After(K),
+
+    /// The number of rows to skip, when the rows are ordered by columns.
+    Offset(u64),
+}
+
+/// Private : For macro only.
+#[doc(hidden)]
+pub struct _MacroPage<'a, K, F> {
+    pub after: Option<&'a PageToken<K>>,
+    pub filter: &'a F,
+    pub key_param: fn(&K) -> Option<&dyn ToSql>,
+    pub keys: &'static [&'static str],
+    pub limit: usize,
+    pub order_by: &'a [OrderBy],
+}
+
+impl<K, F> _MacroPage<'_, K, F>
+where
+    K: Clone,
+{
+    /// A token of the other ordering would restart at the first row.
+    pub fn check_after(&self) -> Result<()> {
+        match (self.after, self.is_keyset()) {
+            (Some(PageToken::After(_)), false) | (Some(PageToken::Offset(_)), true) => Err(
+                Error::Str("The page token does not match the order of the page."),
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    fn is_keyset(&self) -> bool {
+        self.order_by.is_empty() && self.keys.len() == 1
+    }
+
+    fn limit(&self) -> usize {
+        self.limit.max(1)
+    }
+
+    fn offset(&self) -> u64 {
+        match self.after {
+            Some(PageToken::Offset(offset)) if !self.is_keyset() => *offset,
+            _ => 0,
+        }
+    }
+
+    /// Appends the order and the range of the page to the load statement.
+    pub fn order_sql(&self, mut sql: String) -> String {
+        sql.push_str(" ORDER BY ");
+
+        for o in self.order_by {
+            let dir = if o.desc { "DESC" } else { "ASC" };
+            let _ = write!(sql, "t.[{}] {dir}, ", o.column);
+        }
+
+        for key in self.keys {
+            let _ = write!(sql, "t.[{key}], ");
+        }
+
+        sql.truncate(sql.len() - 2);
+
+        let _ = write!(
+            sql,
+            " OFFSET {} ROWS FETCH NEXT {} ROWS ONLY",
+            self.offset(),
+            self.limit() + 1
+        );
+
+        sql
+    }
+
+    /// One more row than the limit is read to know if there is a next page.
+    pub fn page<E>(&self, mut rows: Vec<(K, E)>) -> Page<K, E> {
+        let limit = self.limit();
+
+        if rows.len() <= limit {
+            return Page { rows, next: None };
+        }
+
+        rows.truncate(limit);
+
+        let next = match rows.last() {
+            Some((key, _)) if self.is_keyset() => PageToken::After(key.clone()),
+            _ => PageToken::Offset(self.offset() + limit as u64),
+        };
+
+        Page {
+            rows,
+            next: Some(next),
+        }
+    }
+}
+
+impl<K, F> FilterSql for _MacroPage<'_, K, F>
+where
+    F: FilterSql,
+    K: Clone + Sync,
+{
+    fn filter_sql(&self, param_index: usize) -> (Cow<'_, str>, Cow<'_, [&'_ dyn ToSql]>) {
+        let (sql, params) = self.filter.filter_sql(param_index);
+
+        let key = match (self.after, self.keys) {
+            (Some(PageToken::After(key)), [column]) if self.is_keyset() => {
+                (self.key_param)(key).map(|p| (column, p))
+            }
+            _ => None,
+        };
+
+        let Some((column, key)) = key else {
+            return (sql, params);
+        };
+
+        let mut params = params.into_owned();
+        params.push(key);
+
+        let after = format!("t.[{column}] > @p{}", param_index + params.len());
+
+        let sql = match sql.is_empty() {
+            true => after,
+            false => format!("({sql}) AND {after}"),
+        };
+
+        (Cow::Owned(sql), Cow::Owned(params))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AGE: Column<i32> = Column::new("Age");
+
+    fn page<'a>(
+        after: Option<&'a PageToken<i32>>,
+        order_by: &'a [OrderBy],
+    ) -> _MacroPage<'a, i32, ()> {
+        _MacroPage {
+            after,
+            filter: &(),
+            key_param: |k| Some(k as _),
+            keys: &["Id"],
+            limit: 2,
+            order_by,
+        }
+    }
+
+    #[test]
+    fn keyset() {
+        let after = PageToken::After(5);
+        let page = page(Some(&after), &[]);
+        let (sql, params) = page.filter_sql(0);
+
+        assert_eq!(sql, "t.[Id] > @p1");
+        assert_eq!(params.len(), 1);
+        assert_eq!(
+            page.order_sql("SELECT".into()),
+            "SELECT ORDER BY t.[Id] OFFSET 0 ROWS FETCH NEXT 3 ROWS ONLY"
+        );
+
+        let next = page.page(vec![(6, ()), (7, ()), (8, ())]).next;
+        assert_eq!(next, Some(PageToken::After(7)));
+    }
+
+    #[test]
+    fn mismatched_token() {
+        let offset = PageToken::Offset(4);
+        assert!(page(Some(&offset), &[]).check_after().is_err());
+
+        let after = PageToken::After(5);
+        assert!(page(Some(&after), &[AGE.asc()]).check_after().is_err());
+    }
+
+    #[test]
+    fn offset() {
+        let after = PageToken::Offset(4);
+        let order_by = [AGE.desc()];
+        let page = page(Some(&after), &order_by);
+        let (sql, params) = page.filter_sql(0);
+
+        assert_eq!(sql, "");
+        assert!(params.is_empty());
+        assert_eq!(
+            page.order_sql("SELECT".into()),
+            "SELECT ORDER BY t.[Age] DESC, t.[Id] OFFSET 4 ROWS FETCH NEXT 3 ROWS ONLY"
+        );
+
+        assert!(page.check_after().is_ok());
+        assert_eq!(page.page(vec![(1, ()), (2, ())]).next, None);
+        assert_eq!(
+            page.page(vec![(1, ()), (2, ()), (3, ())]).next,
+            Some(PageToken::Offset(6))
+        );
+    }
+}
@@ -0,0 +1,26 @@
+use crate::{MssqlProvider, ToSql};
+use futures::{StreamExt, TryStreamExt, stream};
+use std::borrow::Cow;
+use storm::{BoxStream, ProviderContainer, Result};
+use tiberius::Row;
+
+/// Private : For macro only.
+#[doc(hidden)]
+pub fn _macro_load_stream<'a, R>(
+    container: &'a ProviderContainer,
+    provider: &'a str,
+    sql: String,
+    params: Cow<'a, [&'a dyn ToSql]>,
+    mapper: fn(Row) -> Result<R>,
+    use_transaction: bool,
+) -> BoxStream<'a, Result<R>>
+where
+    R: Send + 'a,
+{
+    stream::once(async move {
+        let provider = container.provide::<MssqlProvider>(provider).await?;
+        Result::Ok(provider.query_stream(Cow::Owned(sql), params, mapper, use_transaction))
+    })
+    .try_flatten()
+    .boxed()
+}
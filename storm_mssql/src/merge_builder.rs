@@ -0,0 +1,249 @@
+use crate::{
+    Execute, MAX_PARAMS, MssqlProvider, Parameter, Result, ToSql, UpsertBuilder,
+    upsert_builder::MergeColumn,
+};
+use std::fmt::Write;
+use tiberius::TokenRow;
+
+/// The minimum number of rows of a batch to use the bulk load protocol, smaller batches are
+/// sent using a VALUES source.
+const BULK_MIN_ROWS: usize = 1000;
+
+/// The temporary table receiving the rows of a bulk load before they are merged.
+const BULK_TABLE: &str = "#storm_bulk";
+
+/// The maximum number of rows in the VALUES source of a single statement.
+const MAX_ROWS: usize = 1000;
+
+/// Upserts many rows using a `MERGE` statement with a VALUES source, sending the rows in
+/// chunks instead of one statement per row.
+///
+/// Each row is described by an [UpsertBuilder]. Rows having the same columns are merged
+/// together, a row that cannot be merged (identity) is executed on its own.
+pub struct MergeBuilder<'a> {
+    batches: Vec<Batch<'a>>,
+    singles: Vec<UpsertBuilder<'a>>,
+    table: &'a str,
+}
+
+impl<'a> MergeBuilder<'a> {
+    pub fn new(table: &'a str) -> Self {
+        Self {
+            batches: Vec::new(),
+            singles: Vec::new(),
+            table,
+        }
+    }
+
+    pub fn add_row(&mut self, builder: UpsertBuilder<'a>) {
+        if !builder.is_mergeable() {
+            self.singles.push(builder);
+            return;
+        }
+
+        let (columns, params) = builder.into_merge_row();
+
+        match self.batches.iter_mut().find(|b| b.columns == columns) {
+            Some(batch) => batch.rows.push(params),
+            None => self.batches.push(Batch {
+                columns,
+                rows: vec![params],
+            }),
+        }
+    }
+
+    pub async fn execute<P: Execute>(self, provider: &P) -> Result<()> {
+        for batch in &self.batches {
+            for rows in batch.chunks() {
+                let sql = merge_sql(
+                    self.table,
+                    &batch.columns,
+                    &values_sql(&batch.columns, rows.len()),
+                );
+                let params = rows
+                    .iter()
+                    .flatten()
+                    .map(|v| v as _)
+                    .collect::<Vec<&dyn ToSql>>();
+
+                provider.execute(sql, params.as_slice()).await?;
+            }
+        }
+
+        for builder in self.singles {
+            builder.execute(provider).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Like [execute](Self::execute) but the large batches are sent using the bulk load
+    /// protocol in a temporary table before being merged, which is much faster for imports.
+    pub async fn execute_bulk(mut self, provider: &MssqlProvider) -> Result<()> {
+        let (bulks, batches) = self
+            .batches
+            .into_iter()
+            .partition::<Vec<_>, _>(|b| b.rows.len() >= BULK_MIN_ROWS);
+
+        self.batches = batches;
+
+        for batch in bulks {
+            let names = join(batch.columns.iter().map(|c| c.name.to_string()));
+
+            provider
+                .execute(
+                    format!(
+                        "DROP TABLE IF EXISTS {BULK_TABLE}; SELECT TOP 0 {names} INTO {BULK_TABLE} FROM {};",
+                        self.table
+                    ),
+                    &[],
+                )
+                .await?;
+
+            let mut rows = Vec::with_capacity(batch.rows.len());
+
+            for params in batch.rows {
+                let mut row = TokenRow::with_capacity(params.len());
+
+                for param in params {
+                    row.push(param.0);
+                }
+
+                rows.push(row);
+            }
+
+            provider.bulk_insert(BULK_TABLE, rows).await?;
+
+            let sql = format!(
+                "{} DROP TABLE {BULK_TABLE};",
+                merge_sql(
+                    self.table,
+                    &batch.columns,
+                    &format!("(SELECT {names} FROM {BULK_TABLE})")
+                )
+            );
+
+            provider.execute(sql, &[]).await?;
+        }
+
+        self.execute(provider).await
+    }
+
+    /// Returns the statements sent to the database, one per chunk.
+    pub fn sql(&self) -> Vec<String> {
+        let mut vec = Vec::new();
+
+        for batch in &self.batches {
+            for rows in batch.chunks() {
+                vec.push(merge_sql(
+                    self.table,
+                    &batch.columns,
+                    &values_sql(&batch.columns, rows.len()),
+                ));
+            }
+        }
+
+        vec.extend(self.singles.iter().map(UpsertBuilder::sql));
+        vec
+    }
+}
+
+/// Rows having the same columns.
+struct Batch<'a> {
+    columns: Vec<MergeColumn>,
+    rows: Vec<Vec<Parameter<'a>>>,
+}
+
+impl<'a> Batch<'a> {
+    fn chunks(&self) -> std::slice::Chunks<'_, Vec<Parameter<'a>>> {
+        let rows_per_chunk = (MAX_PARAMS / self.columns.len().max(1)).clamp(1, MAX_ROWS);
+        self.rows.chunks(rows_per_chunk)
+    }
+}
+
+fn merge_sql(table: &str, columns: &[MergeColumn], source: &str) -> String {
+    let names = join(columns.iter().map(|c| c.name.to_string()));
+    let sources = join(columns.iter().map(|c| format!("s.{}", c.name)));
+
+    let on = columns
+        .iter()
+        .filter(|c| c.key)
+        .map(|c| format!("t.{0}=s.{0}", c.name))
+        .collect::<Vec<_>>()
+        .join(" AND ");
+
+    let setters = join(
+        columns
+            .iter()
+            .filter(|c| !c.key)
+            .map(|c| format!("t.{0}=s.{0}", c.name)),
+    );
+
+    let update = if setters.is_empty() {
+        String::new()
+    } else {
+        format!("WHEN MATCHED THEN UPDATE SET {setters}")
+    };
+
+    format!(
+        "MERGE {table} WITH (HOLDLOCK) AS t USING {source} AS s ({names}) ON {on} {update} WHEN NOT MATCHED THEN INSERT ({names}) VALUES ({sources});"
+    )
+}
+
+/// Creates a VALUES source with a parameter for each column of each row.
+fn values_sql(columns: &[MergeColumn], rows: usize) -> String {
+    let mut values = String::from("(VALUES ");
+    let mut param = 0;
+
+    for row in 0..rows {
+        if row > 0 {
+            values.push(',');
+        }
+
+        values.push('(');
+
+        for index in 0..columns.len() {
+            if index > 0 {
+                values.push(',');
+            }
+
+            param += 1;
+            let _ = write!(values, "@p{param}");
+        }
+
+        values.push(')');
+    }
+
+    values.push(')');
+    values
+}
+
+fn join(iter: impl Iterator<Item = String>) -> String {
+    iter.collect::<Vec<_>>().join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_rows_with_same_columns() {
+        let ids = [1, 2];
+        let names = ["a".to_string(), "b".to_string()];
+        let mut merge = MergeBuilder::new("[dbo].[T]");
+
+        for (id, name) in ids.iter().zip(&names) {
+            let mut builder = UpsertBuilder::new("[dbo].[T]");
+            builder.add_field_ref("[Name]", name);
+            builder.add_key_ref("[Id]", id);
+            merge.add_row(builder);
+        }
+
+        assert_eq!(
+            merge.sql(),
+            vec![
+                "MERGE [dbo].[T] WITH (HOLDLOCK) AS t USING (VALUES (@p1,@p2),(@p3,@p4)) AS s ([Name],[Id]) ON t.[Id]=s.[Id] WHEN MATCHED THEN UPDATE SET t.[Name]=s.[Name] WHEN NOT MATCHED THEN INSERT ([Name],[Id]) VALUES (s.[Name],s.[Id]);"
+            ]
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use crate::MssqlProvider;
+use crate::{MssqlProvider, MssqlProviderOptions};
 use std::{env::var, ffi::OsStr};
 use storm::{BoxFuture, Error, Result, provider::ProviderFactory};
 use tiberius::Config;
@@ -14,6 +14,13 @@ impl MssqlFactory {
             &var(var_name).map_err(Error::std)?,
         )?))
     }
+
+    pub fn with_options(self, options: MssqlProviderOptions) -> MssqlFactoryWithOptions {
+        MssqlFactoryWithOptions {
+            config: self.0,
+            options,
+        }
+    }
 }
 
 impl ProviderFactory for MssqlFactory {
@@ -23,3 +30,22 @@ impl ProviderFactory for MssqlFactory {
         Box::pin(async move { Ok(MssqlProvider::new(self.0.clone())) })
     }
 }
+
+/// A [MssqlFactory] creating the providers with custom [MssqlProviderOptions].
+pub struct MssqlFactoryWithOptions {
+    pub config: Config,
+    pub options: MssqlProviderOptions,
+}
+
+impl ProviderFactory for MssqlFactoryWithOptions {
+    type Provider = MssqlProvider;
+
+    fn create_provider(&self) -> BoxFuture<'_, Result<Self::Provider>> {
+        Box::pin(async move {
+            Ok(MssqlProvider::with_options(
+                self.config.clone(),
+                self.options.clone(),
+            ))
+        })
+    }
+}
@@ -1,9 +1,16 @@
-use crate::{Client, ClientFactory, Execute, Parameter, QueryRows, ToSql, execute::ExecuteArgs};
+use crate::{
+    Client, ClientFactory, Execute, MssqlProviderOptions, Parameter, PoolOptions, QueryRows, ToSql,
+    client_pool::{ClientPool, PooledClient},
+    error::map_error,
+    execute::ExecuteArgs,
+};
 use chrono::NaiveDateTime;
-use futures::{Stream, StreamExt, TryStreamExt};
+use futures::{FutureExt, SinkExt, Stream, StreamExt, TryStreamExt, channel::mpsc, future, stream};
 use std::{
     borrow::Cow,
     fmt::Debug,
+    future::Future,
+    mem::replace,
     ops::{Deref, DerefMut},
     pin::Pin,
     sync::{
@@ -13,12 +20,13 @@ use std::{
     task::{Context, Poll},
     time::Duration,
 };
-use storm::{BoxFuture, Error, Result, provider};
-use tiberius::Row;
+use storm::{BoxFuture, BoxStream, Error, Result, provider};
+use tiberius::{Row, TokenRow};
 use tokio::sync::{Mutex, MutexGuard};
-use tracing::info;
+use tracing::{info, warn};
 
-pub const DEFAULT_LOCK_TIMEOUT: Duration = Duration::from_secs(90);
+/// The number of rows read ahead of the consumer of a [query_stream](MssqlProvider::query_stream).
+const STREAM_BUFFER: usize = 100;
 
 pub struct MssqlProvider(Arc<Inner>);
 
@@ -27,6 +35,43 @@ impl MssqlProvider {
         (Box::new(client_factory) as Box<dyn ClientFactory>).into()
     }
 
+    /// Creates a provider with a custom pool of clients for the non-transactional operations.
+    pub fn with_pool_options<F: ClientFactory>(client_factory: F, options: PoolOptions) -> Self {
+        Self::with_options(
+            client_factory,
+            MssqlProviderOptions {
+                pool: options,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Creates a provider with custom retry, timeout and reconnect policies.
+    pub fn with_options<F: ClientFactory>(
+        client_factory: F,
+        options: MssqlProviderOptions,
+    ) -> Self {
+        Self::from_factory(Arc::new(client_factory), options)
+    }
+
+    fn from_factory(factory: Arc<dyn ClientFactory>, options: MssqlProviderOptions) -> Self {
+        Self(Arc::new(Inner {
+            cancel_transaction: Default::default(),
+            factory: Arc::clone(&factory),
+            pool: ClientPool::new(options.pool.clone(), options.lock_timeout),
+            state: Mutex::new(State::new(factory, &options)),
+            options,
+        }))
+    }
+
+    /// Indicate if the non-transactional operations use the pool of clients.
+    ///
+    /// A factory operating under a transaction must share its client, all operations
+    /// are then executed on the provider state.
+    fn use_pool(&self, use_transaction: bool) -> bool {
+        !use_transaction && !self.0.factory.under_transaction()
+    }
+
     async fn state(&self) -> MutexGuard<'_, State> {
         let mut guard = self.0.state.lock().await;
 
@@ -92,7 +137,85 @@ impl MssqlProvider {
         Ok(coll)
     }
 
+    /// Executes a query and returns the rows as they are received instead of collecting them.
+    ///
+    /// The client is held until the stream completes, dropping the stream before its end
+    /// discards the client.
+    pub fn query_stream<'a, M, R>(
+        &'a self,
+        sql: Cow<'a, str>,
+        params: Cow<'a, [&'a dyn ToSql]>,
+        mut mapper: M,
+        use_transaction: bool,
+    ) -> BoxStream<'a, Result<R>>
+    where
+        M: FnMut(Row) -> Result<R> + Send + 'a,
+        R: Send + 'a,
+    {
+        let (mut sender, receiver) = mpsc::channel(STREAM_BUFFER);
+
+        // the query is driven by the stream itself, the rows are sent through the channel
+        // so the consumer can apply back pressure.
+        let producer = async move {
+            let r = async {
+                let mut conn = QueryConn::new(self, use_transaction).await?;
+                let mut query = conn.query(&sql, &params).await?;
+
+                while let Some(row) = query.try_next().await? {
+                    let row = mapper(row);
+                    let is_err = row.is_err();
+
+                    if sender.send(row).await.is_err() || is_err {
+                        return Ok(());
+                    }
+                }
+
+                query.complete().await?;
+                conn.complete();
+                Ok(())
+            };
+
+            if let Err(e) = r.await {
+                let _ = sender.send(Err(e)).await;
+            }
+        };
+
+        Box::pin(stream::select(
+            producer.into_stream().filter_map(|()| future::ready(None)),
+            receiver,
+        ))
+    }
+
+    /// Loads the rows in a table using the TDS bulk load protocol, under the transaction.
+    ///
+    /// Each row must contain all the updatable columns of the table, in the order of the table
+    /// and using the exact column types. Returns the number of rows inserted.
+    pub async fn bulk_insert<'a, I>(&self, table: &str, rows: I) -> Result<u64>
+    where
+        I: IntoIterator<Item = TokenRow<'a>>,
+    {
+        let mut guard = self.state().await;
+        let mut client = guard.transaction().await?;
+
+        let count = match timeout(
+            self.0.options.statement_timeout,
+            bulk_insert(&mut client, table, rows),
+        )
+        .await?
+        {
+            Ok(count) => count,
+            Err(e) => {
+                let _ = trace_deadlock(&mut client).await;
+                return Err(e);
+            }
+        };
+
+        guard.restore_transaction(client);
+        Ok(count)
+    }
+
     pub async fn set_client_lock_timeout(&self, timeout: Option<Duration>) -> Result<()> {
+        self.0.pool.set_lock_timeout(timeout);
         self.0.state.lock().await.set_lock_timeout(timeout).await
     }
 }
@@ -113,43 +236,91 @@ impl Execute for MssqlProvider {
 
             adapt_params(params, &mut intermediate, &mut output);
 
-            let mut client;
-            let client_ref;
+            if self.use_pool(args.use_transaction) {
+                let mut pooled = self.0.pool.checkout(&*self.0.factory).await?;
+
+                let count = match timeout(
+                    self.0.options.statement_timeout,
+                    pooled.client.execute(statement, &output),
+                )
+                .await?
+                {
+                    Ok(r) => r.total(),
+                    Err(e) => {
+                        let _ = trace_deadlock(&mut pooled.client).await;
+                        return Err(map_error(e));
+                    }
+                };
+
+                pooled.release();
+                return Ok(count);
+            }
+
             let mut guard = self.state().await;
 
-            if args.use_transaction {
-                client = guard.transaction().await?;
-                client_ref = &mut guard.transaction;
-            } else {
-                client = guard.client().await?;
-                client_ref = &mut guard.client;
-            };
+            let mut client = match args.use_transaction {
+                true => guard.transaction().await,
+                false => guard.client().await,
+            }?;
 
-            let count = match client.execute(statement, &output).await.map(|v| v.total()) {
-                Ok(count) => count,
+            let count = match timeout(
+                self.0.options.statement_timeout,
+                client.execute(statement, &output),
+            )
+            .await?
+            {
+                Ok(r) => r.total(),
                 Err(e) => {
                     let _ = trace_deadlock(&mut client).await;
-                    return Err(e.into());
+                    return Err(map_error(e));
                 }
             };
 
-            *client_ref = Some(client);
+            match args.use_transaction {
+                true => guard.restore_transaction(client),
+                false => guard.client = Some(client),
+            }
+
             Ok(count)
         })
     }
+
+    fn execute_rows<'a, S, M, R, C>(
+        &'a self,
+        statement: S,
+        params: &'a [&'a dyn ToSql],
+        mapper: M,
+        args: ExecuteArgs,
+    ) -> BoxFuture<'a, Result<C>>
+    where
+        C: Default + Extend<R> + Send,
+        M: FnMut(Row) -> Result<R> + Send + 'a,
+        R: Send,
+        S: Debug + Into<Cow<'a, str>> + Send + 'a,
+    {
+        let sql = statement.into();
+
+        Box::pin(async move {
+            timeout(
+                self.0.options.statement_timeout,
+                self.query_rows_imp(&sql, params, mapper, args.use_transaction),
+            )
+            .await?
+        })
+    }
 }
 
 struct Inner {
     cancel_transaction: AtomicBool,
+    factory: Arc<dyn ClientFactory>,
+    options: MssqlProviderOptions,
+    pool: ClientPool,
     state: Mutex<State>,
 }
 
 impl From<Box<dyn ClientFactory>> for MssqlProvider {
     fn from(factory: Box<dyn ClientFactory>) -> Self {
-        Self(Arc::new(Inner {
-            cancel_transaction: Default::default(),
-            state: Mutex::new(State::new(factory)),
-        }))
+        Self::from_factory(factory.into(), MssqlProviderOptions::default())
     }
 }
 
@@ -167,6 +338,18 @@ impl provider::Provider for MssqlProvider {
     fn commit(&self) -> BoxFuture<'_, Result<()>> {
         Box::pin(async move { self.state().await.commit().await })
     }
+
+    fn prepare(&self) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move { self.state().await.prepare().await })
+    }
+
+    fn rollback_to<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move { self.state().await.rollback_to(name).await })
+    }
+
+    fn savepoint<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move { self.state().await.savepoint(name).await })
+    }
 }
 
 impl QueryRows for MssqlProvider {
@@ -186,31 +369,56 @@ impl QueryRows for MssqlProvider {
         let sql = statement.into();
 
         Box::pin(async move {
-            let mut count = 0;
+            let options = &self.0.options;
+            let mut attempt = 1;
+            let mut delay = options.query_retry.backoff;
 
             loop {
-                let r = self
-                    .query_rows_imp(&sql, params, &mut mapper, use_transaction)
-                    .await;
+                let e = match timeout(
+                    options.statement_timeout,
+                    self.query_rows_imp(&sql, params, &mut mapper, use_transaction),
+                )
+                .await
+                {
+                    Ok(Ok(v)) => return Ok(v),
+                    Ok(Err(e)) | Err(e) => e,
+                };
+
+                // the statements of a transaction are lost with its client, running the query
+                // again would start a new transaction.
+                if attempt >= options.query_retry.max_attempts || !self.use_pool(use_transaction) {
+                    return Err(e);
+                }
+
+                warn!(attempt, error = %e, "query failed, retrying");
 
-                if r.is_ok() || count > 5 {
-                    return r;
+                if !delay.is_zero() {
+                    tokio::time::sleep(delay).await;
                 }
 
-                count += 1;
+                attempt += 1;
+                delay = delay.saturating_mul(2).min(options.query_retry.max_backoff);
             }
         })
     }
 }
 
-struct QueryConn<'a> {
-    client: Client,
-    guard: MutexGuard<'a, State>,
-    use_transaction: bool,
+enum QueryConn<'a> {
+    Pooled(PooledClient<'a>),
+    State {
+        client: Client,
+        guard: MutexGuard<'a, State>,
+        use_transaction: bool,
+    },
 }
 
 impl<'a> QueryConn<'a> {
     async fn new(provider: &'a MssqlProvider, use_transaction: bool) -> Result<QueryConn<'a>> {
+        if provider.use_pool(use_transaction) {
+            let pooled = provider.0.pool.checkout(&*provider.0.factory).await?;
+            return Ok(Self::Pooled(pooled));
+        }
+
         let mut guard = provider.state().await;
 
         let client = match use_transaction {
@@ -218,17 +426,31 @@ impl<'a> QueryConn<'a> {
             false => guard.client().await,
         }?;
 
-        Ok(Self {
+        Ok(Self::State {
             client,
             guard,
             use_transaction,
         })
     }
 
-    fn complete(mut self) {
-        match self.use_transaction {
-            true => self.guard.transaction = Some(self.client),
-            false => self.guard.client = Some(self.client),
+    fn client(&mut self) -> &mut Client {
+        match self {
+            Self::Pooled(pooled) => &mut pooled.client,
+            Self::State { client, .. } => client,
+        }
+    }
+
+    fn complete(self) {
+        match self {
+            Self::Pooled(pooled) => pooled.release(),
+            Self::State {
+                client,
+                mut guard,
+                use_transaction,
+            } => match use_transaction {
+                true => guard.restore_transaction(client),
+                false => guard.client = Some(client),
+            },
         }
     }
 
@@ -245,12 +467,29 @@ impl<'a> QueryConn<'a> {
 
         adapt_params(params, &mut intermediate, &mut output);
 
-        let stream = self.client.query(sql, &output[..]).await?;
+        let stream = self
+            .client()
+            .query(sql, &output[..])
+            .await
+            .map_err(map_error)?;
 
         Ok(QueryStream(stream))
     }
 }
 
+async fn bulk_insert<'a, I>(client: &mut Client, table: &str, rows: I) -> Result<u64>
+where
+    I: IntoIterator<Item = TokenRow<'a>>,
+{
+    let mut request = client.bulk_insert(table).await.map_err(map_error)?;
+
+    for row in rows {
+        request.send(row).await.map_err(map_error)?;
+    }
+
+    Ok(request.finalize().await.map_err(map_error)?.total())
+}
+
 async fn trace_deadlock(client: &mut Client) -> Result<()> {
     const SQL: &str = r#"
         SELECT
@@ -308,7 +547,7 @@ struct QueryStream<'a>(tiberius::QueryStream<'a>);
 
 impl QueryStream<'_> {
     async fn complete(self) -> Result<()> {
-        self.0.into_results().await?;
+        self.0.into_results().await.map_err(map_error)?;
         Ok(())
     }
 }
@@ -322,7 +561,7 @@ impl Stream for QueryStream<'_> {
                 Poll::Pending => Poll::Pending,
                 Poll::Ready(Some(Ok(tiberius::QueryItem::Metadata(_)))) => continue,
                 Poll::Ready(Some(Ok(tiberius::QueryItem::Row(r)))) => Poll::Ready(Some(Ok(r))),
-                Poll::Ready(Some(Err(e))) => Poll::Ready(Some(Err(e.into()))),
+                Poll::Ready(Some(Err(e))) => Poll::Ready(Some(Err(map_error(e)))),
                 Poll::Ready(None) => Poll::Ready(None),
             };
         }
@@ -331,17 +570,28 @@ impl Stream for QueryStream<'_> {
 
 struct State {
     client: Option<Client>,
-    factory: Box<dyn ClientFactory>,
+
+    /// Set while the transaction client is in use, it stays set when the client is dropped
+    /// after an error or a timeout. The transaction is then lost and must be cancelled.
+    failed: bool,
+    factory: Arc<dyn ClientFactory>,
     lock_timeout: Option<Duration>,
+    reconnect: bool,
+
+    /// The savepoints created in the current transaction.
+    savepoints: Vec<Box<str>>,
     transaction: Option<Client>,
 }
 
 impl State {
-    fn new(factory: Box<dyn ClientFactory>) -> Self {
+    fn new(factory: Arc<dyn ClientFactory>, options: &MssqlProviderOptions) -> Self {
         Self {
             client: None,
+            failed: false,
             factory,
-            lock_timeout: Some(DEFAULT_LOCK_TIMEOUT),
+            lock_timeout: options.lock_timeout,
+            reconnect: options.reconnect,
+            savepoints: Vec::new(),
             transaction: None,
         }
     }
@@ -351,8 +601,15 @@ impl State {
     }
 
     async fn cancel_or_commit(&mut self, statement: &'static str) -> Result<()> {
+        self.savepoints.clear();
+
+        // the server has rolled back the transaction of the dropped client.
+        if replace(&mut self.failed, false) && statement == "COMMIT" {
+            return Err(Error::ClientInError);
+        }
+
         if let Some(mut client) = self.transaction.take() {
-            let r = client.simple_query(statement).await.map_err(Error::Mssql);
+            let r = client.simple_query(statement).await.map_err(map_error);
 
             #[cfg(feature = "telemetry")]
             {
@@ -387,6 +644,13 @@ impl State {
         }
     }
 
+    fn check_failed(&self) -> Result<()> {
+        match self.failed {
+            true => Err(Error::ClientInError),
+            false => Ok(()),
+        }
+    }
+
     async fn commit(&mut self) -> Result<()> {
         self.cancel_or_commit("COMMIT").await
     }
@@ -399,6 +663,68 @@ impl State {
         Ok(client)
     }
 
+    /// Checks the transaction can still be committed.
+    async fn prepare(&mut self) -> Result<()> {
+        const SQL: &str =
+            "IF XACT_STATE() <> 1 THROW 50000, 'The transaction cannot be committed.', 1;";
+
+        self.check_failed()?;
+
+        if let Some(client) = self.transaction.as_mut() {
+            client
+                .simple_query(SQL)
+                .await
+                .map_err(map_error)?
+                .into_results()
+                .await
+                .map_err(map_error)?;
+        }
+
+        Ok(())
+    }
+
+    /// Rolls back to a savepoint of the current transaction. The whole transaction is rolled back
+    /// when it was started after the savepoint.
+    async fn rollback_to(&mut self, name: &str) -> Result<()> {
+        let Some(index) = self.savepoints.iter().rposition(|n| &**n == name) else {
+            return self.cancel().await;
+        };
+
+        self.check_failed()?;
+
+        self.savepoints.truncate(index + 1);
+
+        if let Some(client) = self.transaction.as_mut() {
+            client
+                .simple_query(format!("ROLLBACK TRANSACTION {name};"))
+                .await
+                .map_err(map_error)?
+                .into_results()
+                .await
+                .map_err(map_error)?;
+        }
+
+        Ok(())
+    }
+
+    async fn savepoint(&mut self, name: &str) -> Result<()> {
+        self.check_failed()?;
+
+        if let Some(client) = self.transaction.as_mut() {
+            client
+                .simple_query(format!("SAVE TRANSACTION {name};"))
+                .await
+                .map_err(map_error)?
+                .into_results()
+                .await
+                .map_err(map_error)?;
+
+            self.savepoints.push(name.into());
+        }
+
+        Ok(())
+    }
+
     async fn set_lock_timeout(&mut self, timeout: Option<Duration>) -> Result<()> {
         if self.lock_timeout != timeout {
             let mut client = self.client.take();
@@ -417,16 +743,30 @@ impl State {
         Ok(())
     }
 
+    fn restore_transaction(&mut self, client: Client) {
+        self.failed = false;
+        self.transaction = Some(client);
+    }
+
+    /// Takes the transaction client, beginning a transaction if there is none. The client must
+    /// be given back with [restore_transaction](Self::restore_transaction).
     async fn transaction(&mut self) -> Result<Client> {
-        match self.transaction.take() {
-            Some(t) => Ok(t),
+        self.check_failed()?;
+
+        let client = match self.transaction.take() {
+            Some(t) => t,
             None => {
+                let reused = self.client.is_some();
                 let mut client = self.client().await?;
+                let mut r = begin_tran(&mut client).await;
 
-                let r = client
-                    .simple_query("BEGIN TRAN")
-                    .await
-                    .map_err(Error::Mssql);
+                // the client kept idle may have been closed by the server.
+                if r.is_err() && reused && self.reconnect && !self.factory.under_transaction() {
+                    warn!("provider client broken, reconnecting");
+
+                    client = self.create_client().await?;
+                    r = begin_tran(&mut client).await;
+                }
 
                 #[cfg(feature = "telemetry")]
                 {
@@ -435,19 +775,42 @@ impl State {
 
                 r?;
 
-                Ok(client)
+                client
             }
-        }
+        };
+
+        self.failed = true;
+        Ok(client)
+    }
+}
+
+async fn begin_tran(client: &mut Client) -> Result<()> {
+    client.simple_query("BEGIN TRAN").await.map_err(map_error)?;
+    Ok(())
+}
+
+/// Awaits a statement for at most `duration`, the client running it must be dropped on
+/// [Error::Timeout] since the statement may still be running.
+async fn timeout<F: Future>(duration: Option<Duration>, f: F) -> Result<F::Output> {
+    match duration {
+        Some(duration) => tokio::time::timeout(duration, f)
+            .await
+            .map_err(|_| Error::Timeout),
+        None => Ok(f.await),
     }
 }
 
-async fn set_client_lock_timeout(client: &mut Client, timeout: Option<Duration>) -> Result<()> {
+pub(crate) async fn set_client_lock_timeout(
+    client: &mut Client,
+    timeout: Option<Duration>,
+) -> Result<()> {
     client
         .simple_query(format!(
             "SET LOCK_TIMEOUT {};",
             timeout.map_or(-1, |d| d.as_millis() as i128)
         ))
-        .await?;
+        .await
+        .map_err(map_error)?;
 
     Ok(())
 }
@@ -0,0 +1,47 @@
+use crate::PoolOptions;
+use std::time::Duration;
+use storm::RetryOptions;
+
+pub const DEFAULT_LOCK_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// The options of the [MssqlProvider](crate::MssqlProvider), to be tuned by deployment.
+#[derive(Clone, Debug)]
+pub struct MssqlProviderOptions {
+    /// The `SET LOCK_TIMEOUT` of the clients, `None` waits indefinitely.
+    pub lock_timeout: Option<Duration>,
+
+    /// The pool of clients used for the non-transactional operations.
+    pub pool: PoolOptions,
+
+    /// The attempts of [query_rows](crate::QueryRows::query_rows) when a query fails. The queries
+    /// run under the transaction are never retried.
+    pub query_retry: RetryOptions,
+
+    /// Reconnects the client kept by the provider when it is found broken while starting
+    /// a transaction.
+    pub reconnect: bool,
+
+    /// The maximum duration of a statement run by `execute`, `query_rows` or `bulk_insert`.
+    ///
+    /// The client is closed when the duration elapses, which cancels the statement on the
+    /// server, and [Error::Timeout](storm::Error::Timeout) is returned. A statement of the
+    /// transaction loses it, the next operations of the transaction fail with
+    /// [Error::ClientInError](storm::Error::ClientInError) until it is cancelled.
+    pub statement_timeout: Option<Duration>,
+}
+
+impl Default for MssqlProviderOptions {
+    fn default() -> Self {
+        Self {
+            lock_timeout: Some(DEFAULT_LOCK_TIMEOUT),
+            pool: PoolOptions::default(),
+            query_retry: RetryOptions {
+                max_attempts: 7,
+                backoff: Duration::ZERO,
+                max_backoff: Duration::ZERO,
+            },
+            reconnect: true,
+            statement_timeout: None,
+        }
+    }
+}
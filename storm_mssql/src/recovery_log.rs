@@ -0,0 +1,102 @@
+use crate::{_macro_load_field, Execute, ExecuteArgs, MssqlProvider, QueryRows, ToSql};
+use storm::{
+    BoxFuture, Error, Result,
+    provider::{CommitRecord, RecoveryLog},
+};
+
+/// A [RecoveryLog] writing the commit records in a SQL Server table, as JSON.
+///
+/// The records are written outside of any transaction, with a provider that must not be
+/// registered in the container committed. The table is created by [create_table](Self::create_table):
+///
+/// ```sql
+/// CREATE TABLE <table> (Id BIGINT NOT NULL PRIMARY KEY, Record NVARCHAR(MAX) NOT NULL)
+/// ```
+///
+/// A record is deleted once all its providers are committed, so the table only holds the
+/// commits left pending, replayed at startup by
+/// [ProviderContainer::recover](storm::ProviderContainer::recover).
+pub struct MssqlRecoveryLog {
+    provider: MssqlProvider,
+    table: Box<str>,
+}
+
+impl MssqlRecoveryLog {
+    pub fn new(provider: MssqlProvider, table: impl Into<Box<str>>) -> Self {
+        Self {
+            provider,
+            table: table.into(),
+        }
+    }
+
+    /// Creates the table of the records if it does not exist.
+    pub async fn create_table(&self) -> Result<()> {
+        let sql = format!(
+            "IF OBJECT_ID('{0}') IS NULL CREATE TABLE {0} (Id BIGINT NOT NULL PRIMARY KEY, Record NVARCHAR(MAX) NOT NULL);",
+            self.table
+        );
+
+        self.execute(sql, &[]).await
+    }
+
+    async fn execute(&self, sql: String, params: &[&dyn ToSql]) -> Result<()> {
+        self.provider
+            .execute_with_args(
+                sql,
+                params,
+                ExecuteArgs {
+                    use_transaction: false,
+                },
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn write(&self, record: &CommitRecord, sql: String) -> Result<()> {
+        let id = record.id as i64;
+        let json = serde_json::to_string(record).map_err(Error::std)?;
+
+        self.execute(sql, &[&id, &json]).await
+    }
+}
+
+impl RecoveryLog for MssqlRecoveryLog {
+    fn prepared<'a>(&'a self, record: &'a CommitRecord) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let sql = format!("INSERT INTO {} (Id, Record) VALUES (@p1, @p2);", self.table);
+            self.write(record, sql).await
+        })
+    }
+
+    fn finished<'a>(&'a self, record: &'a CommitRecord) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            if record.pending.is_empty() {
+                let id = record.id as i64;
+                let sql = format!("DELETE FROM {} WHERE Id = @p1;", self.table);
+                return self.execute(sql, &[&id]).await;
+            }
+
+            let sql = format!("UPDATE {} SET Record = @p2 WHERE Id = @p1;", self.table);
+            self.write(record, sql).await
+        })
+    }
+
+    fn pending(&self) -> BoxFuture<'_, Result<Vec<CommitRecord>>> {
+        Box::pin(async move {
+            let sql = format!("SELECT Record FROM {} ORDER BY Id;", self.table);
+
+            self.provider
+                .query_rows(
+                    sql,
+                    &[],
+                    |row| {
+                        let json: &str = _macro_load_field(&row, 0)?;
+                        serde_json::from_str(json).map_err(Error::std)
+                    },
+                    false,
+                )
+                .await
+        })
+    }
+}
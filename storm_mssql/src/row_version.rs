@@ -0,0 +1,44 @@
+use crate::{FromSql, ToSql, ToSqlNull};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use storm::{Error, Gc, IsDefined, Result};
+use tiberius::ColumnData;
+
+/// The value of a `rowversion` column, used as a concurrency token. A new row has a zero
+/// version until it is saved.
+#[derive(
+    Clone, Copy, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize,
+)]
+pub struct RowVersion(pub u64);
+
+impl<'a> FromSql<'a> for RowVersion {
+    type Column = &'a [u8];
+
+    fn from_sql(col: Option<Self::Column>) -> Result<Self> {
+        let col = col.ok_or(Error::ColumnNull)?;
+        let bytes = <[u8; 8]>::try_from(col)
+            .map_err(|_| Error::ConvertFailed(format!("rowversion of {} bytes", col.len())))?;
+
+        Ok(Self(u64::from_be_bytes(bytes)))
+    }
+}
+
+impl Gc for RowVersion {}
+
+impl IsDefined for RowVersion {
+    fn is_defined(&self) -> bool {
+        self.0 != 0
+    }
+}
+
+impl ToSql for RowVersion {
+    fn to_sql(&self) -> ColumnData<'_> {
+        ColumnData::Binary(Some(Cow::Owned(self.0.to_be_bytes().to_vec())))
+    }
+}
+
+impl ToSqlNull for RowVersion {
+    fn to_sql_null() -> ColumnData<'static> {
+        ColumnData::Binary(None)
+    }
+}
@@ -1,17 +1,35 @@
-use crate::{Client, ClientFactory, MssqlFactory, MssqlProvider};
-use storm::{BoxFuture, Error, Result, provider::ProviderFactory};
+use crate::{
+    Client, ClientFactory, MssqlFactory, MssqlFactoryWithOptions, MssqlProvider,
+    MssqlProviderOptions, error::map_error,
+};
+use storm::{BoxFuture, Result, provider::ProviderFactory};
 
 /// This can wrap a ClientFactory and creates a transaction for each Client that are returned.
 /// It is useful for integration tests making sure that all items are rollback once the test
 /// is done.
 pub struct TransactionScoped<F>(pub(crate) F);
 
+impl TransactionScoped<MssqlFactory> {
+    pub fn with_options(
+        self,
+        options: MssqlProviderOptions,
+    ) -> TransactionScoped<MssqlFactoryWithOptions> {
+        TransactionScoped(self.0.with_options(options))
+    }
+}
+
 impl From<MssqlFactory> for TransactionScoped<MssqlFactory> {
     fn from(f: MssqlFactory) -> Self {
         TransactionScoped(f)
     }
 }
 
+impl From<MssqlFactoryWithOptions> for TransactionScoped<MssqlFactoryWithOptions> {
+    fn from(f: MssqlFactoryWithOptions) -> Self {
+        TransactionScoped(f)
+    }
+}
+
 impl ProviderFactory for TransactionScoped<MssqlFactory> {
     type Provider = MssqlProvider;
 
@@ -20,6 +38,19 @@ impl ProviderFactory for TransactionScoped<MssqlFactory> {
     }
 }
 
+impl ProviderFactory for TransactionScoped<MssqlFactoryWithOptions> {
+    type Provider = MssqlProvider;
+
+    fn create_provider(&self) -> BoxFuture<'_, Result<Self::Provider>> {
+        Box::pin(async move {
+            Ok(MssqlProvider::with_options(
+                TransactionScoped(self.0.config.clone()),
+                self.0.options.clone(),
+            ))
+        })
+    }
+}
+
 impl<F> ClientFactory for TransactionScoped<F>
 where
     F: ClientFactory + Send + Sync,
@@ -28,10 +59,7 @@ where
         Box::pin(async {
             let mut client = self.0.create_client().await?;
 
-            client
-                .simple_query("BEGIN TRAN")
-                .await
-                .map_err(Error::Mssql)?;
+            client.simple_query("BEGIN TRAN").await.map_err(map_error)?;
 
             Ok(client)
         })
@@ -1,11 +1,18 @@
-use crate::{Error, Execute, FromSql, Parameter, QueryRows, Result, ToSql};
+use crate::{Error, Execute, ExecuteArgs, FromSql, Parameter, Result, ToSql};
 use storm::IsDefined;
-use tiberius::ColumnData;
+use tiberius::{ColumnData, Row};
 use tracing::error;
 
+/// The temp table receiving the `OUTPUT INSERTED` columns, an `OUTPUT` clause without `INTO`
+/// is rejected by the tables having triggers.
+const OUTPUT_TABLE: &str = "#storm_output";
+
 pub struct UpsertBuilder<'a> {
+    pub(crate) columns: Vec<MergeColumn>,
+    identity: Option<Box<str>>,
     insert_fields: String,
     insert_values: String,
+    output: Vec<OutputColumn>,
     params: Vec<Parameter<'a>>,
     update_setters: String,
     update_wheres: String,
@@ -16,8 +23,11 @@ pub struct UpsertBuilder<'a> {
 impl<'a> UpsertBuilder<'a> {
     pub fn new(table: &'a str) -> Self {
         Self {
+            columns: Vec::new(),
+            identity: None,
             insert_fields: String::new(),
             insert_values: String::new(),
+            output: Vec::new(),
             params: Vec::new(),
             update_setters: String::new(),
             update_wheres: String::new(),
@@ -43,6 +53,27 @@ impl<'a> UpsertBuilder<'a> {
         self.update_setters.push_str(param);
     }
 
+    /// A `rowversion` column checked by the update, the row is inserted when the token is not
+    /// defined. The new token is read back with `OUTPUT INSERTED`.
+    pub fn add_concurrency_token<T: IsDefined + ToSql>(&mut self, name: &str, value: &'a T) {
+        if value.is_defined() {
+            self.upsert_mode = UpsertMode::Update;
+            self.params.push(Parameter::from_ref(value));
+
+            if !self.update_wheres.is_empty() {
+                self.update_wheres.push_str("AND");
+            }
+
+            let param = &self.param();
+
+            self.add_wheres(name, param);
+        } else {
+            self.upsert_mode = UpsertMode::Insert;
+        }
+
+        self.output.push(OutputColumn::new(name, Some("binary(8)")));
+    }
+
     pub fn add_field_identity<T: IsDefined + ToSql>(&mut self, name: &str, value: T) {
         if value.is_defined() {
             self.upsert_mode = UpsertMode::Update;
@@ -54,11 +85,13 @@ impl<'a> UpsertBuilder<'a> {
     }
 
     pub fn add_field_owned<T: ToSql>(&mut self, name: &str, value: T) {
+        self.columns.push(MergeColumn::new(name, false));
         self.params.push(Parameter::from_owned(value));
         self.add_field(name);
     }
 
     pub fn add_field_ref<T: ToSql>(&mut self, name: &str, value: &'a T) {
+        self.columns.push(MergeColumn::new(name, false));
         self.params.push(Parameter::from_ref(value));
         self.add_field(name);
     }
@@ -68,6 +101,7 @@ impl<'a> UpsertBuilder<'a> {
             self.upsert_mode = UpsertMode::Update;
         } else {
             self.upsert_mode = UpsertMode::Insert;
+            self.identity = Some(name.into());
         }
 
         self.params.push(Parameter::from_owned(value));
@@ -81,7 +115,24 @@ impl<'a> UpsertBuilder<'a> {
         self.add_wheres(name, param);
     }
 
+    /// A key taking the next value of a `SEQUENCE` when it is not defined.
+    pub fn add_key_sequence<T: IsDefined + ToSql>(&mut self, name: &str, sequence: &str, value: T) {
+        if !value.is_defined() {
+            if !self.insert_fields.is_empty() {
+                self.insert_fields.push(',');
+                self.insert_values.push(',');
+            }
+
+            self.insert_fields.push_str(name);
+            self.insert_values.push_str("NEXT VALUE FOR ");
+            self.insert_values.push_str(sequence);
+        }
+
+        self.add_key_identity(name, value);
+    }
+
     pub fn add_key_ref<T: ToSql>(&mut self, name: &str, value: &'a T) {
+        self.columns.push(MergeColumn::new(name, true));
         self.params.push(Parameter::from_ref(value));
 
         if !self.insert_fields.is_empty() {
@@ -101,6 +152,20 @@ impl<'a> UpsertBuilder<'a> {
         self.add_wheres(name, param);
     }
 
+    /// A column set by the server, like a computed or a default-valued column, read back with
+    /// `OUTPUT INSERTED` in the same statement.
+    pub fn add_output(&mut self, name: &str) {
+        self.output.push(OutputColumn::new(name, None));
+    }
+
+    /// An identity column that is not a key, read back with `OUTPUT INSERTED` in the same
+    /// statement.
+    pub fn add_output_identity<T: ToSql>(&mut self, name: &str, value: &T) -> Result<()> {
+        let cast_ty = column_data_to_sql_type(value.to_sql())?;
+        self.output.push(OutputColumn::new(name, Some(cast_ty)));
+        Ok(())
+    }
+
     fn add_wheres(&mut self, name: &str, param: &str) {
         self.update_wheres.push('(');
         self.update_wheres.push_str(name);
@@ -119,48 +184,139 @@ impl<'a> UpsertBuilder<'a> {
     pub async fn execute_identity<K, P>(self, provider: &P, key: &mut K) -> Result<()>
     where
         K: for<'b> FromSql<'b> + ToSql + Send,
-        P: Execute + QueryRows,
+        P: Execute,
     {
-        let sql = self.sql();
-        let params = self.params.iter().map(|v| v as _).collect::<Vec<_>>();
-
-        provider.execute(sql, params.as_slice()).await?;
+        self.execute_identity_output(provider, key).await?;
+        Ok(())
+    }
 
-        if self.upsert_mode == UpsertMode::Insert {
+    /// Executes the statement and reads the inserted identity key into `key`. Returns the row
+    /// of the output columns, the key being the last column.
+    pub async fn execute_identity_output<K, P>(
+        mut self,
+        provider: &P,
+        key: &mut K,
+    ) -> Result<Option<Row>>
+    where
+        K: for<'b> FromSql<'b> + ToSql + Send,
+        P: Execute,
+    {
+        if self.upsert_mode == UpsertMode::Insert
+            && let Some(identity) = self.identity.take()
+        {
             let cast_ty = column_data_to_sql_type(key.to_sql())?;
+            let index = self.output.len();
+
+            self.output.push(OutputColumn {
+                cast: Some(cast_ty),
+                name: identity,
+            });
+
+            let row = self.execute_output(provider).await?;
+            let row = row.ok_or(storm::Error::EntityNotFound)?;
 
-            let one: OneValue<K> = provider
-                .query_rows(
-                    format!("SELECT CAST(@@IDENTITY as {cast_ty})"),
-                    &[],
-                    |row| K::from_sql(row.get(0)),
-                    true,
-                )
-                .await?;
+            *key = crate::_macro_load_field(&row, index)?;
 
-            *key = one.0.ok_or(storm::Error::EntityNotFound)?;
+            return Ok(Some(row));
         }
 
-        Ok(())
+        self.execute_output(provider).await
+    }
+
+    /// Executes the statement and returns the row of the output columns, in the order they
+    /// were added, or `None` if no row was written.
+    pub async fn execute_output<P>(self, provider: &P) -> Result<Option<Row>>
+    where
+        P: Execute,
+    {
+        let sql = self.sql();
+        let params = self.params.iter().map(|v| v as _).collect::<Vec<_>>();
+
+        if self.output.is_empty() {
+            provider.execute(sql, params.as_slice()).await?;
+            return Ok(None);
+        }
+
+        let one: OneValue<Row> = provider
+            .execute_rows(sql, params.as_slice(), Ok, ExecuteArgs::default())
+            .await?;
+
+        Ok(one.0)
     }
 
     fn insert_sql(&self) -> String {
+        let output = self.output_clause();
+
         if self.insert_fields.is_empty() {
             // when there is no fields in the table except an identity column.
-            format!("INSERT INTO {} DEFAULT VALUES", self.table)
+            format!("INSERT INTO {}{output} DEFAULT VALUES", self.table)
         } else {
             format!(
-                "INSERT INTO {} ({}) VALUES ({})",
+                "INSERT INTO {} ({}){output} VALUES ({})",
                 self.table, self.insert_fields, self.insert_values
             )
         }
     }
 
+    /// Returns true if the row can be part of a [MergeBuilder](crate::MergeBuilder).
+    pub(crate) fn is_mergeable(&self) -> bool {
+        self.upsert_mode == UpsertMode::InsertThanUpdate
+            && self.columns.len() == self.params.len()
+            && self.columns.iter().any(|c| c.key)
+    }
+
+    pub(crate) fn into_merge_row(self) -> (Vec<MergeColumn>, Vec<Parameter<'a>>) {
+        (self.columns, self.params)
+    }
+
+    fn output_clause(&self) -> String {
+        if self.output.is_empty() {
+            return String::new();
+        }
+
+        let columns = self
+            .output
+            .iter()
+            .map(|c| format!("INSERTED.{}", c.name))
+            .collect::<Vec<_>>()
+            .join(",");
+
+        format!(" OUTPUT {columns} INTO {OUTPUT_TABLE}")
+    }
+
     fn param(&self) -> String {
         format!("@p{}", self.params.len())
     }
 
     pub fn sql(&self) -> String {
+        let sql = self.statement_sql();
+
+        if self.output.is_empty() {
+            return sql;
+        }
+
+        // the temp table takes the types of the columns, a cast drops the identity property.
+        let columns = self
+            .output
+            .iter()
+            .map(|c| match c.cast {
+                Some(ty) => format!("CAST({0} AS {ty}) AS {0}", c.name),
+                None => c.name.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+
+        format!(
+            "DROP TABLE IF EXISTS {OUTPUT_TABLE};
+            SELECT TOP 0 {columns} INTO {OUTPUT_TABLE} FROM {};
+            {sql};
+            SELECT * FROM {OUTPUT_TABLE};
+            DROP TABLE {OUTPUT_TABLE};",
+            self.table
+        )
+    }
+
+    fn statement_sql(&self) -> String {
         match self.upsert_mode {
             UpsertMode::Insert => self.insert_sql(),
             UpsertMode::InsertThanUpdate => {
@@ -193,13 +349,45 @@ impl<'a> UpsertBuilder<'a> {
             String::new()
         } else {
             format!(
-                "UPDATE {} SET {} WHERE {}",
-                self.table, self.update_setters, self.update_wheres
+                "UPDATE {} SET {}{} WHERE {}",
+                self.table,
+                self.update_setters,
+                self.output_clause(),
+                self.update_wheres
             )
         }
     }
 }
 
+#[derive(Clone, Eq, PartialEq)]
+pub(crate) struct MergeColumn {
+    pub(crate) key: bool,
+    pub(crate) name: Box<str>,
+}
+
+impl MergeColumn {
+    fn new(name: &str, key: bool) -> Self {
+        Self {
+            key,
+            name: name.into(),
+        }
+    }
+}
+
+struct OutputColumn {
+    cast: Option<&'static str>,
+    name: Box<str>,
+}
+
+impl OutputColumn {
+    fn new(name: &str, cast: Option<&'static str>) -> Self {
+        Self {
+            cast,
+            name: name.into(),
+        }
+    }
+}
+
 struct OneValue<T>(Option<T>);
 
 impl<T> Default for OneValue<T> {
@@ -235,3 +423,61 @@ fn column_data_to_sql_type(data: ColumnData<'_>) -> Result<&'static str> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn output_inserted_into_temp_table() {
+        let name = "a".to_string();
+        let mut builder = UpsertBuilder::new("[dbo].[T]");
+
+        builder.add_field_ref("[Name]", &name);
+        builder.add_key_sequence("[Id]", "[dbo].[TSeq]", 0);
+        builder.add_output("[Total]");
+
+        let sql = builder.sql();
+        let sql = sql.split_whitespace().collect::<Vec<_>>().join(" ");
+
+        assert_eq!(
+            sql,
+            "DROP TABLE IF EXISTS #storm_output; SELECT TOP 0 [Total] INTO #storm_output FROM [dbo].[T]; INSERT INTO [dbo].[T] ([Name],[Id]) OUTPUT INSERTED.[Total] INTO #storm_output VALUES (@p1,NEXT VALUE FOR [dbo].[TSeq]); SELECT * FROM #storm_output; DROP TABLE #storm_output;"
+        );
+    }
+
+    #[test]
+    fn update_with_concurrency_token() {
+        let name = "a".to_string();
+        let token = 7u32;
+        let mut builder = UpsertBuilder::new("[dbo].[T]");
+
+        builder.add_field_ref("[Name]", &name);
+        builder.add_concurrency_token("[Version]", &token);
+        builder.add_key_ref("[Id]", &1);
+
+        assert!(builder.sql().contains(
+            "UPDATE [dbo].[T] SET [Name]=@p1 OUTPUT INSERTED.[Version] INTO #storm_output WHERE ([Version]=@p2)AND([Id]=@p3)"
+        ));
+    }
+
+    #[test]
+    fn update_output() {
+        let name = "a".to_string();
+        let mut builder = UpsertBuilder::new("[dbo].[T]");
+
+        builder.add_field_ref("[Name]", &name);
+        builder.add_key_identity("[Id]", 5);
+        assert!(builder.add_output_identity("[Number]", &0i32).is_ok());
+
+        assert!(
+            builder
+                .sql()
+                .contains("SELECT TOP 0 CAST([Number] AS int) AS [Number] INTO #storm_output")
+        );
+
+        assert!(builder.sql().contains(
+            "UPDATE [dbo].[T] SET [Name]=@p1 OUTPUT INSERTED.[Number] INTO #storm_output WHERE ([Id]=@p2)"
+        ));
+    }
+}
@@ -0,0 +1,83 @@
+#![allow(clippy::unwrap_used)]
+
+mod common;
+
+use common::{create_ctx, execute};
+use storm::{MssqlLoad, MssqlSave, Result, prelude::*};
+use uuid::Uuid;
+
+#[tokio::test]
+async fn insert_all_uses_bulk_load() -> Result<()> {
+    async_cell_lock::with_deadlock_check(
+        async move {
+            let ctx = create_ctx();
+
+            execute(
+                &*ctx.read().await?,
+                "DROP TABLE IF EXISTS ##StormBulkInsert;
+                CREATE TABLE ##StormBulkInsert (Id INT NOT NULL PRIMARY KEY, Name NVARCHAR(100) NOT NULL);",
+            )
+            .await?;
+
+            ctx.write().await?.tbl_of::<Entity1>().await?;
+
+            // the row 1 is present in the database but not in the ctx.
+            execute(
+                &*ctx.read().await?,
+                "INSERT ##StormBulkInsert (Id, Name) VALUES (1, 'old');",
+            )
+            .await?;
+
+            {
+                let ctx = ctx.queue().await?;
+                let mut trx = ctx.transaction(Uuid::nil());
+                let mut tbl = trx.tbl_of::<Entity1>().await?;
+
+                let count = tbl
+                    .insert_all((1..=2000).map(|id| {
+                        (
+                            id,
+                            Entity1 {
+                                name: format!("e{id}"),
+                            },
+                        )
+                    }))
+                    .await?;
+
+                assert_eq!(count, 2000);
+
+                let log = trx.commit().await?;
+                ctx.write().await?.apply_log(log);
+            }
+
+            let mut ctx = ctx.write().await?;
+
+            ctx.refresh_tbl_of::<Entity1>().await?;
+
+            let tbl = ctx.tbl_of::<Entity1>().await?;
+
+            assert_eq!(tbl.len(), 2000);
+            assert_eq!(tbl.get(&1).unwrap().name, "e1");
+            assert_eq!(tbl.get(&2000).unwrap().name, "e2000");
+
+            Ok(())
+        },
+        "insert_all_uses_bulk_load",
+    )
+    .await
+}
+
+#[derive(Ctx, Debug, MssqlLoad, MssqlSave, PartialEq)]
+#[storm(
+    table = "##StormBulkInsert",
+    keys = "Id",
+    collection = "hash_table",
+    no_test = true
+)]
+struct Entity1 {
+    name: String,
+}
+
+impl Entity for Entity1 {
+    type Key = i32;
+}
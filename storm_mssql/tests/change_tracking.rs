@@ -0,0 +1,70 @@
+#![allow(clippy::unwrap_used)]
+
+mod common;
+
+use common::{DropOnExit, create_ctx, execute};
+use storm::{MssqlLoad, Result, prelude::*};
+use storm_mssql::sync_tbl_of;
+
+#[tokio::test]
+async fn sync_external_changes() -> Result<()> {
+    async_cell_lock::with_deadlock_check(
+        async move {
+            let _drop = DropOnExit("DROP TABLE IF EXISTS StormChangeTracking;");
+            let ctx = create_ctx();
+            let mut ctx = ctx.write().await?;
+
+            execute(
+                &ctx,
+                "DROP TABLE IF EXISTS StormChangeTracking;
+                CREATE TABLE StormChangeTracking (Id INT NOT NULL PRIMARY KEY, Name NVARCHAR(100) NOT NULL);
+                IF NOT EXISTS (SELECT 1 FROM sys.change_tracking_databases WHERE database_id = DB_ID())
+                    ALTER DATABASE CURRENT SET CHANGE_TRACKING = ON;
+                ALTER TABLE StormChangeTracking ENABLE CHANGE_TRACKING;
+                INSERT StormChangeTracking (Id, Name) VALUES (1, 'a'), (2, 'b');",
+            )
+            .await?;
+
+            ctx.tbl_of::<Entity1>().await?;
+
+            // the first sync refreshes the whole table.
+            assert!(!sync_tbl_of::<Entity1>(&mut ctx).await?);
+
+            execute(
+                &ctx,
+                "UPDATE StormChangeTracking SET Name = 'c' WHERE Id = 1;
+                DELETE StormChangeTracking WHERE Id = 2;
+                INSERT StormChangeTracking (Id, Name) VALUES (3, 'd');",
+            )
+            .await?;
+
+            assert!(sync_tbl_of::<Entity1>(&mut ctx).await?);
+
+            let tbl = ctx.tbl_of::<Entity1>().await?;
+
+            assert_eq!(tbl.get(&1).unwrap().name, "c");
+            assert!(tbl.get(&2).is_none());
+            assert_eq!(tbl.get(&3).unwrap().name, "d");
+
+            Ok(())
+        },
+        "sync_external_changes",
+    )
+    .await
+}
+
+#[derive(Ctx, Debug, MssqlLoad, PartialEq)]
+#[storm(
+    table = "StormChangeTracking",
+    keys = "Id",
+    collection = "hash_table",
+    change_tracking = true,
+    no_test = true
+)]
+struct Entity1 {
+    name: String,
+}
+
+impl Entity for Entity1 {
+    type Key = i32;
+}
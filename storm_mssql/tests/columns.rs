@@ -0,0 +1,40 @@
+#![allow(clippy::unwrap_used)]
+
+use storm::{Entity, MssqlLoad};
+use storm_mssql::FilterSql;
+
+#[test]
+fn columns_follow_rename() {
+    let filter = User::COLUMNS.name.eq("a").and(User::COLUMNS.age.gt(18));
+
+    let (sql, params) = filter.filter_sql(0);
+
+    assert_eq!(sql, "((t.[UserName]=@p1) AND (t.[Age]>@p2))");
+    assert_eq!(params.len(), 2);
+    assert_eq!(User::COLUMNS.name.max_length(), 50);
+}
+
+#[test]
+fn columns_have_single_key() {
+    let (sql, _) = User::COLUMNS.id.eq(1).filter_sql(0);
+
+    assert_eq!(sql, "(t.[Id]=@p1)");
+}
+
+#[derive(MssqlLoad)]
+#[storm(
+    table = "Users",
+    keys = "Id",
+    rename_all = "PascalCase",
+    no_test = true,
+    no_ctx
+)]
+pub struct User {
+    #[storm(column = "UserName", max_length = 50)]
+    pub name: String,
+    pub age: i32,
+}
+
+impl Entity for User {
+    type Key = i32;
+}
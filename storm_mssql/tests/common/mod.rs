@@ -0,0 +1,55 @@
+//! The fixture shared by the tests running against the database of the `DB` env var.
+#![allow(clippy::unwrap_used, dead_code)]
+
+use storm::{Result, prelude::*};
+use storm_mssql::{Execute, ExecuteArgs, MssqlProvider};
+
+pub fn create_ctx() -> QueueRwLock<Ctx> {
+    QueueRwLock::new(create_provider().into(), "ctx")
+}
+
+fn create_provider() -> ProviderContainer {
+    storm_mssql::create_provider_container_from_env("DB", "").unwrap()
+}
+
+/// Executes the sql outside of the transaction, on a connection of the pool. The `##` global
+/// temporary tables created this way are dropped when the test process ends.
+pub async fn execute(ctx: &Ctx, sql: &'static str) -> Result<()> {
+    execute_on(ctx.provider(), sql).await
+}
+
+async fn execute_on(provider: &ProviderContainer, sql: &'static str) -> Result<()> {
+    let provider = provider.provide::<MssqlProvider>("").await?;
+
+    provider
+        .execute_with_args(
+            sql,
+            &[],
+            ExecuteArgs {
+                use_transaction: false,
+            },
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Drops the permanent objects of a test when it ends, even when it fails, for the features a
+/// `##` temporary table does not support like change tracking, triggers and sequences.
+pub struct DropOnExit(pub &'static str);
+
+impl Drop for DropOnExit {
+    fn drop(&mut self) {
+        let sql = self.0;
+
+        // the runtime of the test may be shutting down, the sql runs on a runtime of its own.
+        let _ = std::thread::spawn(move || {
+            tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap()
+                .block_on(async { execute_on(&create_provider(), sql).await })
+        })
+        .join();
+    }
+}
@@ -0,0 +1,100 @@
+#![allow(clippy::unwrap_used)]
+
+mod common;
+
+use common::{create_ctx, execute};
+use storm::{Error, MssqlDelete, MssqlLoad, MssqlSave, Result, prelude::*};
+use storm_mssql::RowVersion;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn concurrency_conflict() -> Result<()> {
+    async_cell_lock::with_deadlock_check(
+        async move {
+            let ctx = create_ctx();
+
+            execute(
+                &*ctx.read().await?,
+                "DROP TABLE IF EXISTS ##StormConcurrency;
+                CREATE TABLE ##StormConcurrency (Id INT NOT NULL PRIMARY KEY, Name NVARCHAR(100) NOT NULL, Version ROWVERSION NOT NULL);",
+            )
+            .await?;
+
+            let e = {
+                let ctx = ctx.queue().await?;
+                let mut trx = ctx.transaction(Uuid::nil());
+                let mut tbl = trx.tbl_of::<Entity1>().await?;
+
+                tbl.insert_mut(1, Entity1::new("a")).await?;
+
+                // the token is refreshed on each update.
+                let mut e = tbl.get(&1).unwrap().clone();
+                let version = e.version;
+                assert_ne!(version, RowVersion::default());
+
+                e.name = "b".to_string();
+                tbl.insert_mut(1, e).await?;
+
+                let e = tbl.get(&1).unwrap().clone();
+                assert_ne!(e.version, version);
+
+                let log = trx.commit().await?;
+                ctx.write().await?.apply_log(log);
+                e
+            };
+
+            // another writer changes the row.
+            execute(
+                &*ctx.read().await?,
+                "UPDATE ##StormConcurrency SET Name = 'c' WHERE Id = 1",
+            )
+            .await?;
+
+            {
+                let ctx = ctx.queue().await?;
+                let mut trx = ctx.transaction(Uuid::nil());
+                let mut tbl = trx.tbl_of::<Entity1>().await?;
+
+                let e = Entity1 {
+                    name: "d".to_string(),
+                    ..e
+                };
+
+                let err = tbl.insert_mut(1, e).await.unwrap_err();
+                assert!(matches!(err, Error::ConcurrencyConflict { .. }));
+            }
+
+            Ok(())
+        },
+        "concurrency_conflict",
+    )
+    .await
+}
+
+#[derive(Clone, Ctx, Debug, MssqlDelete, MssqlLoad, MssqlSave, PartialEq)]
+#[storm(
+    table = "##StormConcurrency",
+    keys = "Id",
+    collection = "hash_table",
+    rename_all = "PascalCase",
+    no_test = true
+)]
+struct Entity1 {
+    name: String,
+
+    #[storm(concurrency_token)]
+    version: RowVersion,
+}
+
+impl Entity1 {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            version: RowVersion::default(),
+        }
+    }
+}
+
+impl Entity for Entity1 {
+    type Key = i32;
+}
@@ -12,3 +12,13 @@ pub struct EntityWithDuplicateKey {
 impl Entity for EntityWithDuplicateKey {
     type Key = i32;
 }
+
+#[derive(MssqlDelete)]
+#[storm(table = "t", keys = "a,b", no_test = true, no_ctx)]
+pub struct EntityWithCompositeKey {
+    pub name: String,
+}
+
+impl Entity for EntityWithCompositeKey {
+    type Key = (i32, i32);
+}
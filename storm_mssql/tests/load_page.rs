@@ -0,0 +1,66 @@
+#![allow(clippy::unwrap_used)]
+
+mod common;
+
+use common::{create_ctx, execute};
+use storm::{MssqlLoad, Result, prelude::*};
+use storm_mssql::{LoadPage, PageToken};
+
+#[tokio::test]
+async fn load_page() -> Result<()> {
+    async_cell_lock::with_deadlock_check(
+        async move {
+            let ctx = create_ctx();
+            let ctx = ctx.read().await?;
+
+            execute(
+                &ctx,
+                "DROP TABLE IF EXISTS ##StormLoadPage;
+                CREATE TABLE ##StormLoadPage (Id INT NOT NULL PRIMARY KEY, Name NVARCHAR(100) NOT NULL);
+                INSERT ##StormLoadPage (Id, Name) VALUES (1, 'e'), (2, 'd'), (3, 'c'), (4, 'b'), (5, 'a');",
+            )
+            .await?;
+
+            let filter = Entity1::COLUMNS.name.ne("c");
+
+            // ordered by key.
+            let page = LoadPage::<Entity1, _>::load_page(ctx.provider(), &filter, &[], None, 2).await?;
+            assert_eq!(page.rows.iter().map(|r| r.0).collect::<Vec<_>>(), [1, 2]);
+            assert_eq!(page.next, Some(PageToken::After(2)));
+
+            let page = LoadPage::<Entity1, _>::load_page(ctx.provider(), &filter, &[], page.next.as_ref(), 2).await?;
+            assert_eq!(page.rows.iter().map(|r| r.0).collect::<Vec<_>>(), [4, 5]);
+            assert_eq!(page.next, None);
+
+            // ordered by name.
+            let order_by = [Entity1::COLUMNS.name.asc()];
+
+            let page = LoadPage::<Entity1, _>::load_page(ctx.provider(), &(), &order_by, None, 3).await?;
+            assert_eq!(page.rows.iter().map(|r| r.0).collect::<Vec<_>>(), [5, 4, 3]);
+            assert_eq!(page.next, Some(PageToken::Offset(3)));
+
+            let page = LoadPage::<Entity1, _>::load_page(ctx.provider(), &(), &order_by, page.next.as_ref(), 3).await?;
+            assert_eq!(page.rows.iter().map(|r| r.0).collect::<Vec<_>>(), [2, 1]);
+            assert_eq!(page.next, None);
+
+            Ok(())
+        },
+        "load_page",
+    )
+    .await
+}
+
+#[derive(Ctx, Debug, MssqlLoad, PartialEq)]
+#[storm(
+    table = "##StormLoadPage",
+    keys = "Id",
+    collection = "hash_table",
+    no_test = true
+)]
+struct Entity1 {
+    name: String,
+}
+
+impl Entity for Entity1 {
+    type Key = i32;
+}
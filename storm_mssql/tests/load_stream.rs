@@ -0,0 +1,57 @@
+#![allow(clippy::unwrap_used)]
+
+mod common;
+
+use common::{create_ctx, execute};
+use futures::TryStreamExt;
+use storm::{MssqlLoad, Result, prelude::*, provider::LoadStream};
+
+#[tokio::test]
+async fn load_stream() -> Result<()> {
+    async_cell_lock::with_deadlock_check(
+        async move {
+            let ctx = create_ctx();
+            let ctx = ctx.read().await?;
+
+            execute(
+                &ctx,
+                "DROP TABLE IF EXISTS ##StormLoadStream;
+                CREATE TABLE ##StormLoadStream (Id INT NOT NULL PRIMARY KEY, Name NVARCHAR(100) NOT NULL);
+                INSERT ##StormLoadStream (Id, Name) VALUES (1, 'a'), (2, 'b'), (3, 'c');",
+            )
+            .await?;
+
+            let filter = ("t.[Id] > @p1", &[&1i32 as _][..]);
+
+            let mut rows: Vec<(i32, Entity1)> =
+                LoadStream::<Entity1, _>::load_stream(ctx.provider(), &filter)
+                    .try_collect()
+                    .await?;
+
+            rows.sort_by_key(|(id, _)| *id);
+
+            assert_eq!(rows.len(), 2);
+            assert_eq!(rows[0].0, 2);
+            assert_eq!(rows[1].1.name, "c");
+
+            Ok(())
+        },
+        "load_stream",
+    )
+    .await
+}
+
+#[derive(Ctx, Debug, MssqlLoad, PartialEq)]
+#[storm(
+    table = "##StormLoadStream",
+    keys = "Id",
+    collection = "hash_table",
+    no_test = true
+)]
+struct Entity1 {
+    name: String,
+}
+
+impl Entity for Entity1 {
+    type Key = i32;
+}
@@ -0,0 +1,105 @@
+#![allow(clippy::unwrap_used)]
+
+mod common;
+
+use common::{DropOnExit, create_ctx, execute};
+use storm::{MssqlDelete, MssqlLoad, MssqlSave, Result, prelude::*};
+use uuid::Uuid;
+
+#[tokio::test]
+async fn output_inserted() -> Result<()> {
+    async_cell_lock::with_deadlock_check(
+        async move {
+            // the trigger and the sequence cannot be created for a temporary table.
+            let _drop = DropOnExit(
+                "DROP TABLE IF EXISTS StormOutput;
+                DROP TABLE IF EXISTS StormOutputAudit;
+                DROP SEQUENCE IF EXISTS StormOutputSeq;",
+            );
+
+            let ctx = create_ctx();
+
+            {
+                let ctx = ctx.read().await?;
+
+                execute(
+                    &ctx,
+                    "DROP TABLE IF EXISTS StormOutput;
+                    DROP TABLE IF EXISTS StormOutputAudit;
+                    DROP SEQUENCE IF EXISTS StormOutputSeq;
+                    CREATE SEQUENCE StormOutputSeq AS INT START WITH 10;
+                    CREATE TABLE StormOutputAudit (Id INT IDENTITY(100, 1) NOT NULL PRIMARY KEY, RowId INT NOT NULL);
+                    CREATE TABLE StormOutput (
+                        Id INT NOT NULL PRIMARY KEY,
+                        Name NVARCHAR(100) NOT NULL,
+                        Created INT NOT NULL DEFAULT 7,
+                        NameLen AS LEN(Name)
+                    );",
+                )
+                .await?;
+
+                // an identity created by a trigger must not be read as the key.
+                execute(
+                    &ctx,
+                    "CREATE TRIGGER StormOutputTrigger ON StormOutput AFTER INSERT AS
+                    INSERT StormOutputAudit (RowId) SELECT Id FROM inserted;",
+                )
+                .await?;
+            }
+
+            let ctx = ctx.queue().await?;
+            let mut trx = ctx.transaction(Uuid::nil());
+
+            let mut tbl = trx.tbl_of::<Entity1>().await?;
+            let (key, _) = tbl.insert_mut(0, Entity1::new("abc")).await?;
+            assert_eq!(key, 10);
+
+            let e = tbl.get(&10).unwrap();
+            assert_eq!((e.created, e.name_len), (7, 3));
+
+            let mut e = e.clone();
+            e.name = "abcde".to_string();
+            tbl.insert_mut(10, e).await?;
+
+            let e = tbl.get(&10).unwrap();
+            assert_eq!((e.created, e.name_len), (7, 5));
+
+            Ok(())
+        },
+        "output_inserted",
+    )
+    .await
+}
+
+#[derive(Clone, Ctx, Debug, MssqlDelete, MssqlLoad, MssqlSave, PartialEq)]
+#[storm(
+    table = "StormOutput",
+    keys = "Id",
+    collection = "hash_table",
+    sequence = "StormOutputSeq",
+    rename_all = "PascalCase",
+    no_test = true
+)]
+struct Entity1 {
+    name: String,
+
+    #[storm(generated)]
+    created: i32,
+
+    #[storm(generated)]
+    name_len: i32,
+}
+
+impl Entity1 {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            created: 0,
+            name_len: 0,
+        }
+    }
+}
+
+impl Entity for Entity1 {
+    type Key = i32;
+}
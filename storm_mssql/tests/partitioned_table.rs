@@ -0,0 +1,52 @@
+#![allow(clippy::unwrap_used)]
+
+mod common;
+
+use common::{create_ctx, execute};
+use storm::{MssqlLoad, Result, prelude::*};
+
+#[tokio::test]
+async fn load_partition() -> Result<()> {
+    async_cell_lock::with_deadlock_check(
+        async move {
+            let ctx = create_ctx();
+            let ctx = ctx.read().await?;
+
+            execute(
+                &ctx,
+                "DROP TABLE IF EXISTS ##StormPartition;
+                CREATE TABLE ##StormPartition (Id INT NOT NULL PRIMARY KEY, FiscalYear INT NOT NULL);
+                INSERT ##StormPartition (Id, FiscalYear) VALUES (1, 2023), (2, 2024), (3, 2024);",
+            )
+            .await?;
+
+            let invoices = ctx.tbl_of_partition::<Invoice>(&2024).await?;
+            let mut keys = invoices.keys().copied().collect::<Vec<_>>();
+            keys.sort_unstable();
+
+            assert_eq!(keys, [2, 3]);
+            assert!(!ctx.tbl_of::<Invoice>().await?.is_loaded(&2023));
+
+            Ok(())
+        },
+        "load_partition",
+    )
+    .await
+}
+
+#[derive(Ctx, Debug, MssqlLoad, PartialEq)]
+#[storm(
+    table = "##StormPartition",
+    keys = "Id",
+    collection = "partitioned_table",
+    partition = "fiscal_year",
+    rename_all = "PascalCase",
+    no_test = true
+)]
+struct Invoice {
+    fiscal_year: i32,
+}
+
+impl Entity for Invoice {
+    type Key = i32;
+}
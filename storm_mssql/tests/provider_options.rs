@@ -0,0 +1,62 @@
+#![allow(clippy::unwrap_used)]
+
+use std::time::Duration;
+use storm::{Error, Result, prelude::*, provider::Provider};
+use storm_mssql::{Execute, ExecuteArgs, MssqlFactory, MssqlProvider, MssqlProviderOptions};
+
+fn provider() -> ProviderContainer {
+    let options = MssqlProviderOptions {
+        statement_timeout: Some(Duration::from_millis(200)),
+        ..Default::default()
+    };
+
+    let factory = MssqlFactory::from_env("DB").unwrap().with_options(options);
+
+    let mut provider = ProviderContainer::new();
+    provider.register("", factory);
+    provider
+}
+
+async fn execute(provider: &MssqlProvider, sql: &'static str) -> Result<u64> {
+    provider
+        .execute_with_args(
+            sql,
+            &[],
+            ExecuteArgs {
+                use_transaction: false,
+            },
+        )
+        .await
+}
+
+#[tokio::test]
+async fn statement_timeout() -> Result<()> {
+    let container = provider();
+    let provider = container.provide::<MssqlProvider>("").await?;
+
+    let r = execute(provider, "WAITFOR DELAY '00:00:02';").await;
+    assert!(matches!(r, Err(Error::Timeout)));
+
+    // the client running the cancelled statement is not reused.
+    execute(provider, "SELECT 1;").await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn transaction_lost_on_timeout() -> Result<()> {
+    let container = provider();
+    let provider = container.provide::<MssqlProvider>("").await?;
+
+    let r = provider.execute("WAITFOR DELAY '00:00:02';", &[]).await;
+    assert!(matches!(r, Err(Error::Timeout)));
+
+    // a new transaction is not started in place of the lost one.
+    let r = provider.execute("SELECT 1;", &[]).await;
+    assert!(matches!(r, Err(Error::ClientInError)));
+
+    provider.cancel();
+    provider.execute("SELECT 1;", &[]).await?;
+
+    Ok(())
+}
@@ -0,0 +1,13 @@
+use rusqlite::{Row, types::Type};
+use storm::{Error, Result};
+
+pub use rusqlite::types::FromSql;
+
+/// Internal used for macros
+#[doc(hidden)]
+pub fn _macro_load_field<T: FromSql>(row: &Row<'_>, index: usize) -> Result<T> {
+    row.get(index).map_err(|e| match e {
+        rusqlite::Error::InvalidColumnType(_, _, Type::Null) => Error::ColumnNull,
+        e => Error::Sqlite(e),
+    })
+}
@@ -0,0 +1,35 @@
+mod execute;
+mod filter_sql;
+mod from_sql;
+mod parameter;
+mod query_rows;
+mod save_entity_part;
+mod sqlite_factory;
+mod sqlite_meta;
+mod sqlite_provider;
+mod to_sql;
+mod upsert_builder;
+
+pub use execute::*;
+pub use filter_sql::*;
+pub use from_sql::{_macro_load_field, FromSql};
+pub use parameter::Parameter;
+pub use query_rows::QueryRows;
+pub use rusqlite;
+pub use save_entity_part::SaveEntityPart;
+pub use sqlite_factory::SqliteFactory;
+pub use sqlite_meta::SqliteMeta;
+pub use sqlite_provider::{DEFAULT_BUSY_TIMEOUT, SqliteProvider};
+use storm::ProviderContainer;
+pub use storm::{Error, Result};
+pub use to_sql::ToSql;
+pub use upsert_builder::UpsertBuilder;
+
+pub fn create_provider_container_from_env(env_var: &str, name: &str) -> Result<ProviderContainer> {
+    let factory = SqliteFactory::from_env(env_var)?;
+
+    let mut container = ProviderContainer::new();
+    container.register(name, factory);
+
+    Ok(container)
+}
@@ -0,0 +1,46 @@
+use crate::ToSql;
+use rusqlite::types::ToSqlOutput;
+
+pub struct Parameter<'a>(Value<'a>);
+
+enum Value<'a> {
+    Error(String),
+    Owned(rusqlite::types::Value),
+    Ref(&'a dyn ToSql),
+}
+
+impl<'a> Parameter<'a> {
+    pub fn from_ref<T: ToSql>(t: &'a T) -> Self {
+        Self(Value::Ref(t))
+    }
+}
+
+impl Parameter<'static> {
+    pub fn from_owned<T: ToSql>(t: T) -> Self {
+        Self::copy(&t)
+    }
+
+    fn copy(t: &dyn ToSql) -> Self {
+        Self(match t.to_sql() {
+            Ok(ToSqlOutput::Borrowed(v)) => Value::Owned(v.into()),
+            Ok(ToSqlOutput::Owned(v)) => Value::Owned(v),
+            Ok(_) => Value::Error("parameter type not supported.".to_string()),
+            Err(e) => Value::Error(e.to_string()),
+        })
+    }
+}
+
+impl rusqlite::ToSql for Parameter<'_> {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        match &self.0 {
+            Value::Error(e) => Err(rusqlite::Error::ToSqlConversionFailure(e.clone().into())),
+            Value::Owned(v) => Ok(ToSqlOutput::Borrowed(v.into())),
+            Value::Ref(v) => v.to_sql(),
+        }
+    }
+}
+
+/// Copies a slice of parameters, so they can be bound on a statement run by a blocking thread.
+pub(crate) fn copy_params(params: &[&dyn ToSql]) -> Vec<Parameter<'static>> {
+    params.iter().map(|p| Parameter::copy(*p)).collect()
+}
@@ -0,0 +1,49 @@
+use crate::ToSql;
+use rusqlite::Row;
+use std::{borrow::Cow, fmt::Debug};
+use storm::{BoxFuture, Result};
+
+pub trait QueryRows {
+    /// Execute a query on the database and returns the rows.
+    ///
+    /// ## Parameters
+    /// - use_transaction: make sure the query is run inside a transaction.
+    ///
+    /// This is useful when loading we need to execute a query and then load the result
+    /// from sql from the same transaction.
+    ///
+    /// The query runs on a blocking thread, which is why the mapper and the rows are `'static`.
+    fn query_rows<'a, S, M, R, C>(
+        &'a self,
+        statement: S,
+        params: &'a [&'a dyn ToSql],
+        mapper: M,
+        use_transaction: bool,
+    ) -> BoxFuture<'a, Result<C>>
+    where
+        C: Default + Extend<R> + Send + 'static,
+        M: FnMut(&Row<'_>) -> Result<R> + Send + 'static,
+        R: Send + 'static,
+        S: Debug + for<'b> Into<Cow<'b, str>> + Send + 'a;
+}
+
+impl<P> QueryRows for &P
+where
+    P: QueryRows + Send + Sync,
+{
+    fn query_rows<'a, S, M, R, C>(
+        &'a self,
+        statement: S,
+        params: &'a [&'a dyn ToSql],
+        mapper: M,
+        use_transaction: bool,
+    ) -> BoxFuture<'a, Result<C>>
+    where
+        C: Default + Extend<R> + Send + 'static,
+        M: FnMut(&Row<'_>) -> Result<R> + Send + 'static,
+        R: Send + 'static,
+        S: Debug + for<'b> Into<Cow<'b, str>> + Send + 'a,
+    {
+        (**self).query_rows(statement, params, mapper, use_transaction)
+    }
+}
@@ -0,0 +1,73 @@
+use crate::{SqliteProvider, sqlite_provider::DEFAULT_BUSY_TIMEOUT};
+use rusqlite::Connection;
+use std::{env::var, ffi::OsStr, path::PathBuf};
+use storm::{BoxFuture, Error, Result, provider::ProviderFactory};
+
+const MEMORY: &str = ":memory:";
+
+/// Creates [SqliteProvider](SqliteProvider) on a database file or on an in-memory database.
+#[derive(Clone, Debug)]
+pub struct SqliteFactory {
+    path: Option<PathBuf>,
+}
+
+impl SqliteFactory {
+    /// Reads the database path from an environment variable. The special value `:memory:`
+    /// creates an in-memory database.
+    pub fn from_env<K>(var_name: K) -> Result<Self>
+    where
+        K: AsRef<OsStr>,
+    {
+        let path = var(var_name).map_err(Error::std)?;
+
+        Ok(match path == MEMORY {
+            true => Self::memory(),
+            false => Self::open(path),
+        })
+    }
+
+    /// An in-memory database, living as long as the provider.
+    ///
+    /// A single connection is used for reads and writes, which means that reads made
+    /// outside of a transaction observe the changes of the pending transaction.
+    pub fn memory() -> Self {
+        Self { path: None }
+    }
+
+    /// A database file, created if it does not exist.
+    ///
+    /// The database is switched to the WAL journal mode so that reads made outside of
+    /// a transaction are not blocked by a pending transaction.
+    pub fn open<P: Into<PathBuf>>(path: P) -> Self {
+        Self {
+            path: Some(path.into()),
+        }
+    }
+
+    pub(crate) fn create_connection(&self) -> Result<Connection> {
+        let conn = match self.path.as_ref() {
+            Some(path) => Connection::open(path)?,
+            None => Connection::open_in_memory()?,
+        };
+
+        conn.busy_timeout(DEFAULT_BUSY_TIMEOUT)?;
+
+        if self.path.is_some() {
+            conn.pragma_update_and_check(None, "journal_mode", "WAL", |_| Ok(()))?;
+        }
+
+        Ok(conn)
+    }
+
+    pub(crate) fn is_memory(&self) -> bool {
+        self.path.is_none()
+    }
+}
+
+impl ProviderFactory for SqliteFactory {
+    type Provider = SqliteProvider;
+
+    fn create_provider(&self) -> BoxFuture<'_, Result<Self::Provider>> {
+        Box::pin(async move { Ok(SqliteProvider::new(self.clone())) })
+    }
+}
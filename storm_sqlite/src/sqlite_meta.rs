@@ -0,0 +1,3 @@
+pub trait SqliteMeta {
+    const TABLE: &'static str;
+}
@@ -0,0 +1,275 @@
+use crate::{
+    Execute, Parameter, QueryRows, SqliteFactory, ToSql, execute::ExecuteArgs,
+    parameter::copy_params,
+};
+use rusqlite::{Connection, Row, params_from_iter};
+use std::{
+    borrow::Cow,
+    fmt::Debug,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering::Relaxed},
+    },
+    time::Duration,
+};
+use storm::{BoxFuture, Error, Result, provider};
+use tokio::{sync::Mutex, task::spawn_blocking};
+
+pub const DEFAULT_BUSY_TIMEOUT: Duration = Duration::from_secs(90);
+
+pub struct SqliteProvider {
+    cancel_transaction: Arc<AtomicBool>,
+    state: Arc<Mutex<State>>,
+}
+
+impl SqliteProvider {
+    pub fn new(factory: SqliteFactory) -> Self {
+        Self {
+            cancel_transaction: Default::default(),
+            state: Arc::new(Mutex::new(State::new(factory))),
+        }
+    }
+
+    /// Runs `f` on the state in a blocking thread, since a statement can wait on a locked
+    /// database for up to the busy timeout.
+    ///
+    /// The state stays locked until `f` returns, even when the future is dropped before.
+    async fn with_state<F, T>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce(&mut State) -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let mut guard = Arc::clone(&self.state).lock_owned().await;
+        let cancel = self.cancel_transaction.swap(false, Relaxed);
+
+        spawn_blocking(move || {
+            if cancel {
+                let _ = guard.cancel();
+            }
+
+            f(&mut guard)
+        })
+        .await
+        .map_err(Error::std)?
+    }
+}
+
+impl Execute for SqliteProvider {
+    fn execute_with_args<'a, S>(
+        &'a self,
+        statement: S,
+        params: &'a [&'a dyn ToSql],
+        args: ExecuteArgs,
+    ) -> BoxFuture<'a, Result<u64>>
+    where
+        S: Debug + Into<Cow<'a, str>> + Send + 'a,
+    {
+        let statement = statement.into().into_owned();
+        let params = copy_params(params);
+
+        Box::pin(self.with_state(move |state| {
+            let conn = match args.use_transaction {
+                true => state.transaction()?,
+                false => state.client()?,
+            };
+
+            Ok(conn.execute(&statement, params_from_iter(params.iter()))? as u64)
+        }))
+    }
+}
+
+impl provider::Provider for SqliteProvider {
+    fn cancel(&self) {
+        self.cancel_transaction.store(true, Relaxed);
+
+        // rollback right away when the provider is idle, otherwise the next
+        // operation takes care of it.
+        if let Ok(mut guard) = Arc::clone(&self.state).try_lock_owned() {
+            let cancel = Arc::clone(&self.cancel_transaction);
+
+            spawn_blocking(move || {
+                if cancel.swap(false, Relaxed) {
+                    let _ = guard.cancel();
+                }
+            });
+        }
+    }
+
+    fn commit(&self) -> BoxFuture<'_, Result<()>> {
+        Box::pin(self.with_state(State::commit))
+    }
+
+    fn rollback_to<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<()>> {
+        let name = name.to_owned();
+        Box::pin(self.with_state(move |state| state.rollback_to(&name)))
+    }
+
+    fn savepoint<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<()>> {
+        let name = name.to_owned();
+        Box::pin(self.with_state(move |state| state.savepoint(&name)))
+    }
+}
+
+impl QueryRows for SqliteProvider {
+    fn query_rows<'a, S, M, R, C>(
+        &'a self,
+        statement: S,
+        params: &'a [&'a dyn ToSql],
+        mut mapper: M,
+        use_transaction: bool,
+    ) -> BoxFuture<'a, Result<C>>
+    where
+        C: Default + Extend<R> + Send + 'static,
+        M: FnMut(&Row<'_>) -> Result<R> + Send + 'static,
+        R: Send + 'static,
+        S: Debug + for<'b> Into<Cow<'b, str>> + Send + 'a,
+    {
+        let sql = statement.into().into_owned();
+        let params = copy_params(params);
+
+        Box::pin(self.with_state(move |state| {
+            let conn = match use_transaction {
+                true => state.transaction()?,
+                false => state.client()?,
+            };
+
+            query_rows_imp(conn, &sql, &params, &mut mapper)
+        }))
+    }
+}
+
+fn query_rows_imp<M, R, C>(
+    conn: &Connection,
+    sql: &str,
+    params: &[Parameter<'_>],
+    mapper: &mut M,
+) -> Result<C>
+where
+    C: Default + Extend<R>,
+    M: FnMut(&Row<'_>) -> Result<R>,
+{
+    let mut stmt = conn.prepare_cached(sql)?;
+    let mut rows = stmt.query(params_from_iter(params.iter()))?;
+    let mut vec = Vec::with_capacity(10);
+    let mut coll = C::default();
+
+    while let Some(row) = rows.next()? {
+        vec.push(mapper(row)?);
+
+        if vec.len() == 10 {
+            #[allow(clippy::iter_with_drain)]
+            coll.extend(vec.drain(..));
+        }
+    }
+
+    if !vec.is_empty() {
+        coll.extend(vec);
+    }
+
+    Ok(coll)
+}
+
+struct State {
+    /// Connection used to read outside of a transaction on a database file.
+    client: Option<Connection>,
+    factory: SqliteFactory,
+    in_transaction: bool,
+    /// The savepoints created in the current transaction.
+    savepoints: Vec<Box<str>>,
+    /// Connection used for writes and transactions.
+    transaction: Option<Connection>,
+}
+
+impl State {
+    fn new(factory: SqliteFactory) -> Self {
+        Self {
+            client: None,
+            factory,
+            in_transaction: false,
+            savepoints: Vec::new(),
+            transaction: None,
+        }
+    }
+
+    fn cancel(&mut self) -> Result<()> {
+        self.cancel_or_commit("ROLLBACK")
+    }
+
+    fn cancel_or_commit(&mut self, statement: &'static str) -> Result<()> {
+        if !self.in_transaction {
+            return Ok(());
+        }
+
+        self.in_transaction = false;
+        self.savepoints.clear();
+
+        if let Some(conn) = self.transaction.as_ref()
+            && let Err(e) = conn.execute_batch(statement)
+        {
+            // closing the connection rollbacks the pending transaction.
+            self.transaction = None;
+            return Err(e.into());
+        }
+
+        Ok(())
+    }
+
+    fn client(&mut self) -> Result<&Connection> {
+        if self.factory.is_memory() {
+            return self.connection();
+        }
+
+        let conn = match self.client.take() {
+            Some(c) => c,
+            None => self.factory.create_connection()?,
+        };
+
+        Ok(self.client.insert(conn))
+    }
+
+    fn commit(&mut self) -> Result<()> {
+        self.cancel_or_commit("COMMIT")
+    }
+
+    fn connection(&mut self) -> Result<&Connection> {
+        let conn = match self.transaction.take() {
+            Some(c) => c,
+            None => self.factory.create_connection()?,
+        };
+
+        Ok(self.transaction.insert(conn))
+    }
+
+    /// Rolls back to a savepoint of the current transaction. The whole transaction is rolled back
+    /// when it was started after the savepoint.
+    fn rollback_to(&mut self, name: &str) -> Result<()> {
+        let Some(index) = self.savepoints.iter().rposition(|n| &**n == name) else {
+            return self.cancel();
+        };
+
+        self.savepoints.truncate(index + 1);
+        self.connection()?
+            .execute_batch(&format!("ROLLBACK TO {name}"))?;
+
+        Ok(())
+    }
+
+    fn savepoint(&mut self, name: &str) -> Result<()> {
+        if self.in_transaction {
+            self.connection()?
+                .execute_batch(&format!("SAVEPOINT {name}"))?;
+            self.savepoints.push(name.into());
+        }
+
+        Ok(())
+    }
+
+    fn transaction(&mut self) -> Result<&Connection> {
+        if !self.in_transaction {
+            self.connection()?.execute_batch("BEGIN")?;
+            self.in_transaction = true;
+        }
+
+        self.connection()
+    }
+}
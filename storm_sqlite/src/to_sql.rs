@@ -0,0 +1,20 @@
+use rusqlite::types::ToSqlOutput;
+
+/// A value that can be bound as a parameter of a SQLite statement.
+///
+/// This trait is implemented for every [rusqlite::ToSql](rusqlite::ToSql) that can be shared
+/// between threads. To use a custom type as a parameter, implement
+/// [rusqlite::ToSql](rusqlite::ToSql) on it.
+pub trait ToSql: Send + Sync {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>>;
+}
+
+impl<T> ToSql for T
+where
+    T: rusqlite::ToSql + Send + Sync + ?Sized,
+{
+    #[inline]
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        rusqlite::ToSql::to_sql(self)
+    }
+}
@@ -0,0 +1,225 @@
+use crate::{_macro_load_field, Execute, FromSql, Parameter, QueryRows, Result, ToSql};
+use storm::IsDefined;
+
+pub struct UpsertBuilder<'a> {
+    insert_fields: String,
+    insert_values: String,
+    params: Vec<Parameter<'a>>,
+    update_setters: String,
+    update_wheres: String,
+    upsert_mode: UpsertMode,
+    table: &'a str,
+}
+
+impl<'a> UpsertBuilder<'a> {
+    pub fn new(table: &'a str) -> Self {
+        Self {
+            insert_fields: String::new(),
+            insert_values: String::new(),
+            params: Vec::new(),
+            update_setters: String::new(),
+            update_wheres: String::new(),
+            upsert_mode: UpsertMode::InsertThanUpdate,
+            table,
+        }
+    }
+
+    fn add_field(&mut self, name: &str) {
+        if !self.insert_fields.is_empty() {
+            self.insert_fields.push(',');
+            self.insert_values.push(',');
+        }
+
+        if !self.update_setters.is_empty() {
+            self.update_setters.push(',');
+        }
+
+        let param = &self.param();
+
+        self.insert_fields.push_str(name);
+        self.insert_values.push_str(param);
+
+        self.update_setters.push_str(name);
+        self.update_setters.push('=');
+        self.update_setters.push_str(param);
+    }
+
+    pub fn add_field_identity<T: IsDefined + ToSql>(&mut self, name: &str, value: T) {
+        if value.is_defined() {
+            self.upsert_mode = UpsertMode::Update;
+            self.params.push(Parameter::from_owned(value));
+            self.add_field(name);
+        } else {
+            self.upsert_mode = UpsertMode::Insert;
+        }
+    }
+
+    pub fn add_field_owned<T: ToSql>(&mut self, name: &str, value: T) {
+        self.params.push(Parameter::from_owned(value));
+        self.add_field(name);
+    }
+
+    pub fn add_field_ref<T: ToSql>(&mut self, name: &str, value: &'a T) {
+        self.params.push(Parameter::from_ref(value));
+        self.add_field(name);
+    }
+
+    pub fn add_key_identity<T: IsDefined + ToSql>(&mut self, name: &str, value: T) {
+        // SQLite rejects parameters that are not referenced by the statement,
+        // the key is only bound when the row is updated.
+        if value.is_defined() {
+            self.upsert_mode = UpsertMode::Update;
+            self.params.push(Parameter::from_owned(value));
+
+            let param = &self.param();
+
+            self.add_wheres(name, param);
+        } else {
+            self.upsert_mode = UpsertMode::Insert;
+        }
+    }
+
+    pub fn add_key_ref<T: ToSql>(&mut self, name: &str, value: &'a T) {
+        self.params.push(Parameter::from_ref(value));
+
+        if !self.insert_fields.is_empty() {
+            self.insert_fields.push(',');
+            self.insert_values.push(',');
+        }
+
+        let param = &self.param();
+
+        self.insert_fields.push_str(name);
+        self.insert_values.push_str(param);
+
+        self.add_wheres(name, param);
+    }
+
+    fn add_wheres(&mut self, name: &str, param: &str) {
+        if !self.update_wheres.is_empty() {
+            self.update_wheres.push_str(" AND ");
+        }
+
+        self.update_wheres.push('(');
+        self.update_wheres.push_str(name);
+        self.update_wheres.push('=');
+        self.update_wheres.push_str(param);
+        self.update_wheres.push(')');
+    }
+
+    pub async fn execute<P: Execute>(self, provider: &P) -> Result<()> {
+        let params = self.params.iter().map(|v| v as _).collect::<Vec<_>>();
+
+        match self.upsert_mode {
+            // SQLite has no control flow statement, the update is tried first and
+            // the insert is executed when no row has been affected.
+            UpsertMode::InsertThanUpdate if !self.update_setters.is_empty() => {
+                if provider
+                    .execute(self.update_sql(), params.as_slice())
+                    .await?
+                    == 0
+                {
+                    provider
+                        .execute(self.insert_sql(), params.as_slice())
+                        .await?;
+                }
+            }
+            UpsertMode::InsertThanUpdate => {
+                provider
+                    .execute(self.insert_if_not_exists_sql(), params.as_slice())
+                    .await?;
+            }
+            UpsertMode::Insert => {
+                provider
+                    .execute(self.insert_sql(), params.as_slice())
+                    .await?;
+            }
+            UpsertMode::Update if !self.update_setters.is_empty() => {
+                provider
+                    .execute(self.update_sql(), params.as_slice())
+                    .await?;
+            }
+            UpsertMode::Update => {}
+        }
+
+        Ok(())
+    }
+
+    pub async fn execute_identity<K, P>(self, provider: &P, key: &mut K) -> Result<()>
+    where
+        K: FromSql + ToSql + Send + 'static,
+        P: Execute + QueryRows,
+    {
+        let is_insert = self.upsert_mode == UpsertMode::Insert;
+
+        self.execute(provider).await?;
+
+        if is_insert {
+            let one: OneValue<K> = provider
+                .query_rows(
+                    "SELECT last_insert_rowid()".to_string(),
+                    &[],
+                    |row| _macro_load_field(row, 0),
+                    true,
+                )
+                .await?;
+
+            *key = one.0.ok_or(storm::Error::EntityNotFound)?;
+        }
+
+        Ok(())
+    }
+
+    fn insert_if_not_exists_sql(&self) -> String {
+        format!(
+            "INSERT INTO {} ({}) SELECT {} WHERE NOT EXISTS(SELECT 1 FROM {} WHERE {})",
+            self.table, self.insert_fields, self.insert_values, self.table, self.update_wheres
+        )
+    }
+
+    fn insert_sql(&self) -> String {
+        if self.insert_fields.is_empty() {
+            // when there is no fields in the table except an identity column.
+            format!("INSERT INTO {} DEFAULT VALUES", self.table)
+        } else {
+            format!(
+                "INSERT INTO {} ({}) VALUES ({})",
+                self.table, self.insert_fields, self.insert_values
+            )
+        }
+    }
+
+    fn param(&self) -> String {
+        format!("?{}", self.params.len())
+    }
+
+    fn update_sql(&self) -> String {
+        format!(
+            "UPDATE {} SET {} WHERE {}",
+            self.table, self.update_setters, self.update_wheres
+        )
+    }
+}
+
+struct OneValue<T>(Option<T>);
+
+impl<T> Default for OneValue<T> {
+    fn default() -> Self {
+        OneValue(None)
+    }
+}
+
+impl<T> Extend<T> for OneValue<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        if self.0.is_none() {
+            self.0 = iter.into_iter().next();
+        }
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq)]
+enum UpsertMode {
+    InsertThanUpdate,
+    Insert,
+    Update,
+}
@@ -0,0 +1,104 @@
+#![allow(clippy::unwrap_used)]
+
+use storm::{Result, SqliteDelete, SqliteLoad, SqliteSave, prelude::*};
+use storm_sqlite::{Execute, ExecuteArgs, SqliteFactory, SqliteProvider};
+use uuid::Uuid;
+
+fn create_ctx() -> QueueRwLock<Ctx> {
+    QueueRwLock::new(provider().into(), "ctx")
+}
+
+fn provider() -> ProviderContainer {
+    let mut provider = ProviderContainer::new();
+    provider.register("", SqliteFactory::memory());
+    provider
+}
+
+#[tokio::test]
+async fn identity_key_crud() -> Result<()> {
+    async_cell_lock::with_deadlock_check(async move {
+        let ctx = create_ctx();
+        let ctx = ctx.read().await?;
+        let provider = ctx.provider().provide::<SqliteProvider>("").await?;
+
+        provider
+            .execute_with_args(
+                "CREATE TABLE Tbl (Id INTEGER PRIMARY KEY AUTOINCREMENT, Name TEXT NOT NULL, Other INT NULL);",
+                &[],
+                ExecuteArgs {
+                    use_transaction: false,
+                },
+            )
+            .await?;
+
+        let ctx = ctx.queue().await?;
+        let mut trx = ctx.transaction(Uuid::nil());
+        let mut entities1 = trx.tbl_of::<Entity1>().await?;
+
+        let e1 = Entity1 {
+            name: "E1".to_string(),
+            o: None,
+        };
+
+        // insert
+        let i1 = 0;
+        let (i1, _) = entities1.insert_mut(i1, e1).await?;
+
+        assert_eq!(i1, 1);
+
+        let mut e1 = entities1.get(&i1).unwrap().clone();
+
+        e1.o = Some(5);
+
+        // update
+        entities1.insert_mut(i1, e1).await?;
+
+        let e2 = Entity1 {
+            name: "E2".to_string(),
+            o: None,
+        };
+
+        let i2 = 0;
+
+        let (i2, _) = entities1.insert_mut(i2, e2).await?;
+
+        assert_eq!(i2, 2);
+
+        // delete
+        entities1.remove(i2).await?;
+
+        let log = trx.commit().await?;
+
+        let mut ctx = ctx.write().await?;
+
+        ctx.apply_log(log);
+
+        let ctx = ctx.read().await?;
+        let entities1 = ctx.tbl_of::<Entity1>().await?;
+
+        assert_eq!(
+            entities1.get(&1).unwrap().clone(),
+            Entity1 {
+                name: "E1".to_string(),
+                o: Some(5),
+            }
+        );
+
+        assert!(entities1.get(&2).is_none());
+
+        Ok(())
+    }, "identity_key_crud").await
+}
+
+#[derive(Clone, Ctx, Debug, PartialEq, SqliteDelete, SqliteLoad, SqliteSave)]
+#[storm(table = "Tbl", keys = "Id", collection = "hash_table", identity = "id")]
+struct Entity1 {
+    name: String,
+
+    #[storm(column = "Other")]
+    o: Option<i32>,
+}
+
+impl Entity for Entity1 {
+    type Key = i32;
+}
@@ -0,0 +1,144 @@
+#![allow(clippy::unwrap_used)]
+
+use storm::{Result, SqliteDelete, SqliteLoad, SqliteSave, prelude::*, provider::LoadOne};
+use storm_sqlite::{Execute, ExecuteArgs, SqliteFactory, SqliteProvider};
+use uuid::Uuid;
+
+fn create_ctx() -> QueueRwLock<Ctx> {
+    QueueRwLock::new(provider().into(), "ctx")
+}
+
+fn provider() -> ProviderContainer {
+    let mut provider = ProviderContainer::new();
+    provider.register("", SqliteFactory::memory());
+    provider
+}
+
+#[tokio::test]
+async fn crud() -> Result<()> {
+    async_cell_lock::with_deadlock_check(async move {
+        let ctx = create_ctx();
+        let ctx = ctx.read().await?;
+        let provider = ctx.provider().provide::<SqliteProvider>("").await?;
+
+        provider
+            .execute_with_args(
+                "CREATE TABLE Tbl (Id INT NOT NULL PRIMARY KEY, Name TEXT NOT NULL, Other INT NULL);",
+                &[],
+                ExecuteArgs {
+                    use_transaction: false,
+                },
+            )
+            .await?;
+
+        let ctx = ctx.queue().await?;
+        let mut trx = ctx.transaction(Uuid::nil());
+        let mut entities1 = trx.tbl_of::<Entity1>().await?;
+
+        let e1 = Entity1 {
+            name: "E1".to_string(),
+            o: None,
+        };
+
+        // insert
+        entities1.insert(1, e1).await?;
+
+        let mut e1 = entities1.get(&1).unwrap().clone();
+
+        e1.o = Some(5);
+
+        // update
+        entities1.insert(1, e1).await?;
+
+        let e2 = Entity1 {
+            name: "E2".to_string(),
+            o: None,
+        };
+        entities1.insert(2, e2).await?;
+
+        // delete
+        entities1.remove(2).await?;
+
+        let log = trx.commit().await?;
+
+        let mut ctx = ctx.write().await?;
+
+        ctx.apply_log(log);
+
+        let ctx = ctx.read().await?;
+        let entities1 = ctx.tbl_of::<Entity1>().await?;
+
+        assert_eq!(
+            entities1.get(&1).unwrap().clone(),
+            Entity1 {
+                name: "E1".to_string(),
+                o: Some(5),
+            }
+        );
+
+        assert!(entities1.get(&2).is_none());
+
+        // the database must match the committed state.
+        let e1: Option<Entity1> = ctx.provider().load_one(&1).await?;
+        let e2: Option<Entity1> = ctx.provider().load_one(&2).await?;
+
+        assert_eq!(e1.unwrap().o, Some(5));
+        assert!(e2.is_none());
+
+        Ok(())
+    }, "crud")
+    .await
+}
+
+#[tokio::test]
+async fn rollback_on_drop() -> Result<()> {
+    async_cell_lock::with_deadlock_check(async move {
+        let ctx = create_ctx();
+        let ctx = ctx.read().await?;
+        let provider = ctx.provider().provide::<SqliteProvider>("").await?;
+
+        provider
+            .execute_with_args(
+                "CREATE TABLE Tbl (Id INT NOT NULL PRIMARY KEY, Name TEXT NOT NULL, Other INT NULL);",
+                &[],
+                ExecuteArgs {
+                    use_transaction: false,
+                },
+            )
+            .await?;
+
+        let ctx = ctx.queue().await?;
+
+        {
+            let mut trx = ctx.transaction(Uuid::nil());
+            let mut entities1 = trx.tbl_of::<Entity1>().await?;
+
+            let e1 = Entity1 {
+                name: "E1".to_string(),
+                o: None,
+            };
+
+            entities1.insert(1, e1).await?;
+        }
+
+        let e1: Option<Entity1> = ctx.provider().load_one(&1).await?;
+
+        assert!(e1.is_none());
+
+        Ok(())
+    }, "rollback_on_drop")
+    .await
+}
+
+#[derive(Clone, Ctx, Debug, PartialEq, SqliteDelete, SqliteLoad, SqliteSave)]
+#[storm(table = "Tbl", keys = "Id", collection = "hash_table")]
+struct Entity1 {
+    name: String,
+
+    #[storm(column = "Other")]
+    o: Option<i32>,
+}
+
+impl Entity for Entity1 {
+    type Key = i32;
+}